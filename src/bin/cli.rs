@@ -32,8 +32,24 @@ pub fn create_arg_parser<'a, 'b>() -> App<'a, 'b> {
                         .arg(Arg::with_name("file")
                                  .required(false)
                                  .takes_value(true)
+                                 .multiple(true)
                                  .global(true)
-                                 .help("Input file which shall be read (default: stdin)"))
+                                 .help("Input files which shall be read, - or stdin is standard input (default: stdin)"))
+                        .arg(Arg::with_name("continuous")
+                                 .long("continuous")
+                                 .required(false)
+                                 .help("Continues the addresses of a file where the previous file ended, as if the files were concatenated"))
+                        .arg(Arg::with_name("hex")
+                                 .long("hex")
+                                 .required(false)
+                                 .takes_value(true)
+                                 .conflicts_with("hex_input")
+                                 .help("Uses the given hex text (e.g. \"DE AD be ef\", \"0xDE,0xAD\") as input data"))
+                        .arg(Arg::with_name("hex_input")
+                                 .short("x")
+                                 .long("hex-input")
+                                 .required(false)
+                                 .help("Input file contains hex text instead of binary data"))
                         .arg(Arg::with_name("plain_hexdump")
                                  .short("p")
                                  .long("plain-hexdump")
@@ -49,7 +65,122 @@ pub fn create_arg_parser<'a, 'b>() -> App<'a, 'b> {
                                  .possible_value("bin")
                                  .possible_value("oct")
                                  .possible_value("dec")
-                                 .help("Specifies the output format for the value (default: hex)"))
+                                 .use_delimiter(true)
+                                 .multiple(true)
+                                 .number_of_values(1)
+                                 .help("Specifies the output format for the value, a comma separated list shows one pane per format (default: hex)"))
+                        .arg(Arg::with_name("export")
+                                 .long("export")
+                                 .required(false)
+                                 .takes_value(true)
+                                 .possible_value("ndjson")
+                                 .possible_value("json")
+                                 .possible_value("csv")
+                                 .conflicts_with_all(&["plain_hexdump", "autoskip"])
+                                 .help("Writes one record per line with the offset, bytes, formatted groups and interpretation"))
+                        .arg(Arg::with_name("annotate")
+                                 .long("annotate")
+                                 .required(false)
+                                 .takes_value(true)
+                                 .value_name("file")
+                                 .conflicts_with_all(&["export", "html", "ruler", "autoskip", "plain_hexdump"])
+                                 .help("Colors or brackets the regions listed in <file>, CSV lines of offset,length,label[,color] \
+                                        or a JSON array of objects with these fields, and labels the lines they start on"))
+                        .arg(Arg::with_name("ruler")
+                                 .long("ruler")
+                                 .required(false)
+                                 .takes_value(true)
+                                 .min_values(0)
+                                 .require_equals(true)
+                                 .value_name("lines")
+                                 .conflicts_with_all(&["export", "plain_hexdump"])
+                                 .help("Shows a header labelling every column with its offset within the line, repeated every <lines> lines if given (--ruler=<lines>), the interpretation isn't labelled with --control caret"))
+                        .arg(Arg::with_name("html")
+                                 .long("html")
+                                 .required(false)
+                                 .conflicts_with_all(&["plain_hexdump", "export", "color"])
+                                 .help("Writes a self-contained HTML page, every line is linked by its address (e.g. #0x1A0)"))
+                        .arg(Arg::with_name("color")
+                                 .long("color")
+                                 .required(false)
+                                 .takes_value(true)
+                                 .possible_value("always")
+                                 .possible_value("never")
+                                 .possible_value("auto")
+                                 .help("Colors the output by byte class, auto colors if the output is a terminal (default: auto)"))
+                        .arg(Arg::with_name("values")
+                                 .long("values")
+                                 .required(false)
+                                 .takes_value(true)
+                                 .possible_values(&["u8", "i8", "u16", "i16", "u32", "i32", "u64", "i64", "f32", "f64"])
+                                 .help("Adds a pane showing the data decoded as values of the given type, use -e for little-endian values"))
+                        .arg(Arg::with_name("values_only")
+                                 .long("values-only")
+                                 .required(false)
+                                 .requires("values")
+                                 .help("Shows the decoded values instead of the bytes"))
+                        .arg(Arg::with_name("code_page")
+                                 .long("code-page")
+                                 .required(false)
+                                 .takes_value(true)
+                                 .possible_value("ascii")
+                                 .possible_value("latin1")
+                                 .possible_value("ebcdic")
+                                 .possible_value("cp437")
+                                 .possible_value("utf8")
+                                 .possible_value("utf16le")
+                                 .possible_value("utf16be")
+                                 .help("Specifies the code page of the interpretation column (default: ascii)"))
+                        .arg(Arg::with_name("control_style")
+                                 .long("control")
+                                 .required(false)
+                                 .takes_value(true)
+                                 .possible_value("dot")
+                                 .possible_value("picture")
+                                 .possible_value("caret")
+                                 .possible_value("symbol")
+                                 .help("Specifies how control characters are shown in the interpretation column (default: dot)"))
+                        .arg(Arg::with_name("ebcdic")
+                                 .short("E")
+                                 .long("ebcdic")
+                                 .required(false)
+                                 .conflicts_with("code_page")
+                                 .help("Shows the interpretation column in EBCDIC, same as --code-page ebcdic"))
+                        .arg(Arg::with_name("little_endian")
+                                 .short("e")
+                                 .long("little-endian")
+                                 .required(false)
+                                 .help("Shows the bytes of every group in little-endian order (default group size: 4, columns: 4)"))
+                        .arg(Arg::with_name("autoskip")
+                                 .short("a")
+                                 .long("autoskip")
+                                 .required(false)
+                                 .help("Replaces runs of identical lines with a single '*' line"))
+                        .arg(Arg::with_name("address_format")
+                                 .long("address-format")
+                                 .required(false)
+                                 .takes_value(true)
+                                 .possible_value("Hex")
+                                 .possible_value("hex")
+                                 .possible_value("oct")
+                                 .possible_value("dec")
+                                 .help("Specifies the format of the addresses (default: hex addresses in the case of the values)"))
+                        .arg(Arg::with_name("address_width")
+                                 .long("address-width")
+                                 .required(false)
+                                 .takes_value(true)
+                                 .help("Minimum amount of address digits, grows to fit the size of the input (default: 8)"))
+                        .arg(Arg::with_name("display_offset")
+                                 .short("d")
+                                 .long("display-offset")
+                                 .required(false)
+                                 .takes_value(true)
+                                 .help("Adds <offset> to the displayed addresses, e.g. the load address 0x08000000 of an image"))
+                        .arg(Arg::with_name("file_offset")
+                                 .long("file-offset")
+                                 .required(false)
+                                 .requires("display_offset")
+                                 .help("Shows the offset within the file in front of the displayed address"))
                         .arg(Arg::with_name("group-size")
                                  .short("g")
                                  .long("group-size")
@@ -62,13 +193,163 @@ pub fn create_arg_parser<'a, 'b>() -> App<'a, 'b> {
                                  .required(false)
                                  .takes_value(true)
                                  .help("Specifies the amount of output columns")))
+        .subcommand(SubCommand::with_name("revert")
+                        .about("Reverts a dump back into its binary representation")
+                        .arg(Arg::with_name("file")
+                                 .required(false)
+                                 .takes_value(true)
+                                 .global(true)
+                                 .help("Dump which shall be reverted, - or stdin is standard input (default: stdin)"))
+                        .arg(Arg::with_name("plain_hexdump")
+                                 .short("p")
+                                 .long("plain-hexdump")
+                                 .required(false)
+                                 .help("input is a postscript plain hexdump, line breaks are ignored."))
+                        .arg(Arg::with_name("source")
+                                 .long("source")
+                                 .required(false)
+                                 .conflicts_with("plain_hexdump")
+                                 .help("input is a source file containing an array (see generate)"))
+                        .arg(Arg::with_name("from")
+                                 .long("from")
+                                 .required(false)
+                                 .takes_value(true)
+                                 .possible_value("xxd")
+                                 .possible_value("hexdump")
+                                 .possible_value("od")
+                                 .help("Specifies the tool which created the dump, xxd (also xxd-rs), \
+                                        hexdump -C or od -A x -t x1z (default: detected)"))
+                        .arg(Arg::with_name("little_endian")
+                                 .short("e")
+                                 .long("little-endian")
+                                 .required(false)
+                                 .conflicts_with_all(&["plain_hexdump", "source"])
+                                 .help("input is a little-endian dump (see dump -e)"))
+                        .arg(Arg::with_name("group-size")
+                                 .short("g")
+                                 .long("group-size")
+                                 .required(false)
+                                 .takes_value(true)
+                                 .requires("little_endian")
+                                 .help("Group size of the little-endian dump (default: 4)"))
+                        .arg(Arg::with_name("columns")
+                                 .short("c")
+                                 .long("columns")
+                                 .required(false)
+                                 .takes_value(true)
+                                 .requires("little_endian")
+                                 .help("Amount of groups per line of the little-endian dump (default: 4)"))
+                        .arg(Arg::with_name("address_format")
+                                 .long("address-format")
+                                 .required(false)
+                                 .takes_value(true)
+                                 .possible_value("Hex")
+                                 .possible_value("hex")
+                                 .possible_value("oct")
+                                 .possible_value("dec")
+                                 .help("Specifies the format of the addresses of the dump (default: hex)"))
+                        .arg(Arg::with_name("display_offset")
+                                 .short("d")
+                                 .long("display-offset")
+                                 .required(false)
+                                 .takes_value(true)
+                                 .help("Subtracts <offset> from the addresses, the display offset of the dump (see dump -d)"))
+                        .arg(Arg::with_name("lenient")
+                                 .long("lenient")
+                                 .required(false)
+                                 .help("Skips malformed lines with a warning instead of aborting"))
+                        .arg(Arg::with_name("format")
+                                 .short("f")
+                                 .long("format")
+                                 .required(false)
+                                 .takes_value(true)
+                                 .possible_value("Hex")
+                                 .possible_value("hex")
+                                 .possible_value("bin")
+                                 .possible_value("oct")
+                                 .possible_value("dec")
+                                 .help("Specifies the value format of the dump (default: hex)")))
+        .subcommand(SubCommand::with_name("diff")
+                        .about("Dumps multiple inputs side by side and highlights their differences")
+                        .arg(Arg::with_name("files")
+                                 .required(true)
+                                 .takes_value(true)
+                                 .multiple(true)
+                                 .min_values(2)
+                                 .help("Inputs which shall be compared, the first one is the reference"))
+                        .arg(Arg::with_name("context")
+                                 .short("C")
+                                 .long("context")
+                                 .required(false)
+                                 .takes_value(true)
+                                 .help("Amount of unchanged lines shown around changed ones (default: 1)"))
+                        .arg(Arg::with_name("all")
+                                 .long("all")
+                                 .required(false)
+                                 .conflicts_with("context")
+                                 .help("Shows all lines instead of the changed ones"))
+                        .arg(Arg::with_name("mask")
+                                 .short("m")
+                                 .long("mask")
+                                 .required(false)
+                                 .takes_value(true)
+                                 .help("File of address ranges which are ignored, one <start>-<end> or <start>+<length> per line"))
+                        .arg(Arg::with_name("format")
+                                 .short("f")
+                                 .long("format")
+                                 .required(false)
+                                 .takes_value(true)
+                                 .possible_values(&["Hex", "hex", "bin", "oct", "dec"])
+                                 .help("Specifies the output format for the value (default: hex)"))
+                        .arg(Arg::with_name("color")
+                                 .long("color")
+                                 .required(false)
+                                 .takes_value(true)
+                                 .possible_values(&["always", "never", "auto"])
+                                 .help("Colors the differences, otherwise they are marked by ^ (default: auto)"))
+                        .arg(Arg::with_name("group-size")
+                                 .short("g")
+                                 .long("group-size")
+                                 .required(false)
+                                 .takes_value(true)
+                                 .help("Separate  the output of every <bytes> bytes by a whitespace."))
+                        .arg(Arg::with_name("columns")
+                                 .short("c")
+                                 .long("columns")
+                                 .required(false)
+                                 .takes_value(true)
+                                 .help("Specifies the amount of output columns")))
+        .subcommand(SubCommand::with_name("scan")
+                        .about("Extracts hex dumps embedded in arbitrary text like log files")
+                        .arg(Arg::with_name("file")
+                                 .required(false)
+                                 .takes_value(true)
+                                 .global(true)
+                                 .help("Text which shall be scanned, - or stdin is standard input (default: stdin)"))
+                        .arg(Arg::with_name("split")
+                                 .long("split")
+                                 .required(false)
+                                 .requires("outfile")
+                                 .help("Writes every dump to its own file <output-file>.<first-line>-<last-line> \
+                                        instead of concatenating them")))
         .subcommand(SubCommand::with_name("generate")
                         .about("Generates a source file containing the specified file as array")
                         .arg(Arg::with_name("file")
                                  .required(false)
                                  .takes_value(true)
                                  .global(true)
-                                 .help("Input file which shall be read (default: stdin)"))
+                                 .help("Input file which shall be read, - or stdin is standard input (default: stdin)"))
+                        .arg(Arg::with_name("hex")
+                                 .long("hex")
+                                 .required(false)
+                                 .takes_value(true)
+                                 .conflicts_with("hex_input")
+                                 .help("Uses the given hex text (e.g. \"DE AD be ef\", \"0xDE,0xAD\") as input data"))
+                        .arg(Arg::with_name("hex_input")
+                                 .short("x")
+                                 .long("hex-input")
+                                 .required(false)
+                                 .help("Input file contains hex text instead of binary data"))
                         .arg(Arg::with_name("template")
                                  .short("t")
                                  .long("template")
@@ -1,13 +1,23 @@
 use anyhow::Context;
 use cli::create_arg_parser;
-use xxd::dump::{dump_iterator, Config, Format};
+use xxd::annotate::{self, Annotator};
+use xxd::codepage::CodePage;
+use xxd::convert::{self, Dialect, LineReader, ParseError, Scanner};
+use xxd::diff::{self, Diff};
+use xxd::dump::{
+    dump_iterator, AddressFormat, Config, ControlStyle, Endianness, Format, ValueType, MAX_PANES,
+};
+use xxd::export::{export_iterator, ExportFormat};
 use xxd::generate::{Render, Template};
+use xxd::multi::{self, MultiDump};
 
 use clap::ArgMatches;
 
 use std::fmt::Display;
+use std::fs::OpenOptions;
 use std::io;
 use std::io::BufReader;
+use std::io::IsTerminal;
 use std::io::{Read, Write};
 use std::process::exit;
 
@@ -17,34 +27,91 @@ mod cli;
 fn main() {
     #[cfg(not(target_env = "musl"))]
     human_panic::setup_panic!();
-    let matches = create_arg_parser().get_matches();
+    let matches = match create_arg_parser().get_matches_safe() {
+        Ok(matches) => matches,
+        Err(e) if e.use_stderr() => {
+            eprintln!("{}", e.message);
+            exit(error_status(invoked_subcommand().as_deref()))
+        }
+        Err(e) => e.exit(),
+    };
     match run(&matches) {
-        Ok(_) => exit(0),
+        Ok(status) => exit(status),
         Err(e) => {
             report_error(&e);
-            exit(1)
+            exit(error_status(matches.subcommand_name()))
+        }
+    }
+}
+
+/// Exit status of `subcommand` if an error occurred.
+fn error_status(subcommand: Option<&str>) -> i32 {
+    // like cmp and diff, the diff subcommand reports errors with 2, as 1 means the inputs differ
+    match subcommand {
+        Some("diff") => 2,
+        _ => 1,
+    }
+}
+
+/// Name of the subcommand on the command line, for arguments clap failed to parse.
+fn invoked_subcommand() -> Option<String> {
+    let mut args = std::env::args().skip(1);
+    while let Some(arg) = args.next() {
+        match arg.as_str() {
+            // the global options which take a separate value (see `create_arg_parser`)
+            "-o" | "--output-file" | "-s" | "--seek" | "-l" | "--length" => {
+                args.next();
+            }
+            _ if arg.starts_with('-') => {}
+            _ => return Some(arg),
         }
     }
+    None
 }
 
-fn run(args: &ArgMatches) -> Result<(), anyhow::Error> {
+/// Runs the subcommand and returns the exit status.
+fn run(args: &ArgMatches) -> Result<i32, anyhow::Error> {
     match args.subcommand_name() {
-        Some("dump") => dump(args.subcommand_matches("dump")),
-        Some("generate") => generate(args.subcommand_matches("generate")),
+        Some("dump") => dump(args.subcommand_matches("dump")).map(|_| 0),
+        Some("generate") => generate(args.subcommand_matches("generate")).map(|_| 0),
+        Some("revert") => revert(args.subcommand_matches("revert")).map(|_| 0),
+        Some("scan") => scan(args.subcommand_matches("scan")).map(|_| 0),
+        Some("diff") => diff(args.subcommand_matches("diff")),
         _ => Err(anyhow::anyhow!(args.usage().to_string())),
     }
 }
 
+/// Opens the file at `path` for reading, `-` (or `stdin`) is standard input.
 pub fn create_reader(path: String) -> Result<Box<dyn io::Read>, anyhow::Error> {
     match path.as_ref() {
-        "stdin" => Ok(Box::new(std::io::stdin())),
+        path if multi::is_stdin(path) => Ok(Box::new(std::io::stdin())),
         _ => {
-            let file_reader = std::fs::File::open(path)?;
+            let file_reader =
+                std::fs::File::open(&path).map_err(|e| anyhow::anyhow!("{}: {}", path, e))?;
             Ok(Box::new(file_reader))
         }
     }
 }
 
+/// Creates the reader for the input data of the dump and generate subcommands.
+///
+/// The data is either read from `input_file` or given as hex text (`--hex`, `--hex-input`).
+fn create_input_reader(
+    args: &ArgMatches,
+    input_file: &str,
+) -> Result<Box<dyn io::Read>, anyhow::Error> {
+    if let Some(text) = args.value_of("hex") {
+        return Ok(Box::new(io::Cursor::new(convert::hex_text(text)?)));
+    }
+    let mut reader = create_reader(input_file.to_string())?;
+    if args.is_present("hex_input") {
+        let mut text = String::new();
+        reader.read_to_string(&mut text)?;
+        return Ok(Box::new(io::Cursor::new(convert::hex_text(&text)?)));
+    }
+    Ok(reader)
+}
+
 pub fn create_writer(path: String) -> Result<Box<dyn io::Write>, anyhow::Error> {
     match path.as_ref() {
         "stdout" => Ok(Box::new(std::io::stdout())),
@@ -58,40 +125,167 @@ pub fn create_writer(path: String) -> Result<Box<dyn io::Write>, anyhow::Error>
 fn dump<'a>(args: Option<&ArgMatches<'a>>) -> Result<(), anyhow::Error> {
     let args = args.context("No arguments available")?;
     let output_file = args.value_of("outfile").unwrap_or("stdout");
-    let input_file = args.value_of("file").unwrap_or("stdin");
     let seek = args.value_of("seek").unwrap_or("0").parse::<usize>()?;
-    let length = args.value_of("length");
+    let length = match args.value_of("length") {
+        Some(n) => n.parse::<usize>()?,
+        None => usize::MAX,
+    };
     let settings = create_dump_settings(args)?;
-    let reader = create_reader(input_file.to_string())?;
-    let reader = BufReader::new(reader);
+    let files = input_files(args);
     let mut writer = create_writer(output_file.to_string())?;
-    match length {
-        None => dump_iterator(reader.bytes().skip(seek).flatten(), &mut *writer, settings),
-        Some(n) => dump_iterator(
-            reader
-                .bytes()
-                .skip(seek)
-                .take(n.parse::<usize>()?)
-                .flatten(),
-            &mut *writer,
-            settings,
-        ),
+    let export = args
+        .value_of("export")
+        .map(|format| ExportFormat::from(format.to_string()));
+    if (export.is_some() || args.is_present("html")) && files.len() > 1 {
+        anyhow::bail!("--export and --html support a single input file");
+    }
+    let annotations = match args.value_of("annotate") {
+        Some(path) => {
+            let text = std::fs::read_to_string(path)?;
+            let annotations = annotate::parse_annotations(&text)
+                .with_context(|| format!("invalid annotations {}", path))?;
+            Some(annotations)
+        }
+        None => None,
+    };
+    let mut multi_dump =
+        MultiDump::new(settings, files.len()).continuous(args.is_present("continuous"));
+    for file in files.iter() {
+        let reader = BufReader::new(create_input_reader(args, file)?);
+        let bytes = reader.bytes().skip(seek).take(length).flatten();
+        multi_dump.dump(file, bytes, &mut *writer, |bytes, writer, settings| match (
+            export,
+            &annotations,
+        ) {
+            (Some(format), _) => export_iterator(bytes, writer, settings, format),
+            (None, Some(annotations)) => {
+                Annotator::new(settings, annotations.clone()).dump(bytes, writer)
+            }
+            (None, None) => dump_iterator(bytes, writer, settings),
+        })?;
+    }
+    Ok(())
+}
+
+/// Input files of the dump subcommand, standard input if no file is given.
+fn input_files<'a>(args: &'a ArgMatches) -> Vec<&'a str> {
+    match args.values_of("file") {
+        Some(files) => files.collect(),
+        None => vec!["-"],
     }
 }
 
 fn create_dump_settings<'a>(args: &ArgMatches<'a>) -> Result<Config, anyhow::Error> {
-    let columns = args.value_of("columns").unwrap_or("8").parse::<usize>()?;
-    let format = args.value_of("format").unwrap_or("hex");
-    let address = args.value_of("seek").unwrap_or("0").parse::<usize>()?;
-    let group_size = args
-        .value_of("group-size")
-        .unwrap_or("2")
+    let mut formats: Vec<Format> = Vec::new();
+    let given = args
+        .values_of("format")
+        .map_or(0, |formats| formats.count());
+    if given > MAX_PANES {
+        anyhow::bail!(
+            "at most {} formats can be shown side by side, {} were given",
+            MAX_PANES,
+            given
+        );
+    }
+    for format in args.values_of("format").into_iter().flatten() {
+        let format = Format::from(format.to_string());
+        if !formats.contains(&format) {
+            formats.push(format);
+        }
+    }
+    if formats.is_empty() {
+        formats.push(Format::Hex);
+    }
+    let address = args.value_of("seek").unwrap_or("0").parse::<u64>()?;
+    let display_offset = convert::parse_number(args.value_of("display_offset").unwrap_or("0"))?;
+    let address_width = args
+        .value_of("address_width")
+        .unwrap_or("8")
         .parse::<usize>()?;
+    let endianness = if args.is_present("little_endian") {
+        Endianness::Little
+    } else {
+        Endianness::Big
+    };
+    let value_type = args
+        .value_of("values")
+        .map(|value_type| ValueType::from(value_type.to_string()));
+    // groups match the decoded values by default
+    let default_group_size = match (value_type, endianness) {
+        (Some(value_type), _) => value_type.size(),
+        (None, Endianness::Little) => 4,
+        (None, Endianness::Big) => 2,
+    };
+    let group_size = match args.value_of("group-size") {
+        Some(size) => size.parse::<usize>()?,
+        None => default_group_size,
+    };
+    if group_size == 0 {
+        anyhow::bail!("the group size must be at least 1 byte");
+    }
+    if let Some(value_type) = value_type {
+        // a value must not span two groups, otherwise it doesn't line up with its bytes
+        if group_size % value_type.size() != 0 {
+            anyhow::bail!(
+                "the group size must be a multiple of {} bytes, the size of the values",
+                value_type.size()
+            );
+        }
+    }
+    // like xxd -e, little-endian lines hold 16 bytes
+    let default_columns = match (value_type, endianness) {
+        (None, Endianness::Big) => 8,
+        _ => std::cmp::max(1, 16 / group_size),
+    };
+    let columns = match args.value_of("columns") {
+        Some(columns) => columns.parse::<usize>()?,
+        None => default_columns,
+    };
+    if columns == 0 {
+        anyhow::bail!("the amount of columns must be at least 1");
+    }
+    let color = match args.value_of("color").unwrap_or("auto") {
+        "always" => true,
+        "never" => false,
+        _ => args.value_of("outfile").is_none() && io::stdout().is_terminal(),
+    };
+    let code_page = if args.is_present("ebcdic") {
+        CodePage::Ebcdic
+    } else {
+        CodePage::from(args.value_of("code_page").unwrap_or("ascii").to_string())
+    };
+    let ruler = match args.value_of("ruler") {
+        Some(interval) => Some(interval.parse::<usize>()?),
+        None if args.is_present("ruler") => Some(0),
+        None => None,
+    };
     let settings = Config::new()
-        .format(Format::from(format.to_string()))
+        .formats(&formats)
         .group_size(group_size)
         .columns(columns)
-        .set_address(address);
+        .endianness(endianness)
+        .color(color)
+        .autoskip(args.is_present("autoskip"))
+        .code_page(code_page)
+        .values(value_type)
+        .show_bytes(!args.is_present("values_only"))
+        .control_style(ControlStyle::from(
+            args.value_of("control_style").unwrap_or("dot").to_string(),
+        ))
+        .set_address(address)
+        .display_offset(display_offset)
+        .show_file_offset(args.is_present("file_offset"))
+        .address_width(address_width)
+        .html(args.is_present("html"))
+        .ruler(ruler);
+    let settings = match args.value_of("address_format") {
+        Some(fmt) => settings.address_format(AddressFormat::from(fmt.to_string())),
+        None => settings,
+    };
+    let settings = match dumped_size(args)? {
+        Some(size) => settings.fit_address((address + size).saturating_sub(1)),
+        None => settings,
+    };
     if args.is_present("plain_hexdump") {
         Ok(settings
             .separator(false)
@@ -102,13 +296,36 @@ fn create_dump_settings<'a>(args: &ArgMatches<'a>) -> Result<Config, anyhow::Err
     }
 }
 
+/// Amount of bytes which will be dumped, if the inputs are files whose size is known up front.
+///
+/// With continuous addresses the sizes of all files add up, otherwise the largest one counts.
+fn dumped_size(args: &ArgMatches) -> Result<Option<u64>, anyhow::Error> {
+    if args.is_present("hex") || args.is_present("hex_input") {
+        return Ok(None);
+    }
+    let seek = args.value_of("seek").unwrap_or("0").parse::<u64>()?;
+    let length = match args.value_of("length") {
+        Some(length) => length.parse::<u64>()?,
+        None => u64::MAX,
+    };
+    let sizes: Vec<Option<u64>> = input_files(args)
+        .into_iter()
+        .map(multi::file_size)
+        .collect();
+    Ok(multi::dumped_size(
+        &sizes,
+        seek,
+        length,
+        args.is_present("continuous"),
+    ))
+}
+
 fn generate<'a>(args: Option<&ArgMatches<'a>>) -> Result<(), anyhow::Error> {
     let args = args.context("No arguments available")?;
     let output_file = args.value_of("outfile").unwrap_or("stdout");
-    let input_file = args.value_of("file").unwrap_or("stdin");
     let seek = args.value_of("seek").unwrap_or("0").parse::<usize>()?;
     let length = args.value_of("length");
-    let reader = create_reader(input_file.to_string())?;
+    let reader = create_input_reader(args, args.value_of("file").unwrap_or("-"))?;
     let reader = BufReader::new(reader);
     let mut writer = create_writer(output_file.to_string())?;
     let lang = xxd::generate::Language::from(args.value_of("template").unwrap_or("c"));
@@ -141,6 +358,145 @@ fn generate<'a>(args: Option<&ArgMatches<'a>>) -> Result<(), anyhow::Error> {
     Ok(())
 }
 
+fn revert<'a>(args: Option<&ArgMatches<'a>>) -> Result<(), anyhow::Error> {
+    let args = args.context("No arguments available")?;
+    let output_file = args.value_of("outfile").unwrap_or("stdout");
+    let input_file = args.value_of("file").unwrap_or("-");
+    let reader = create_reader(input_file.to_string())?;
+    let mut reader = BufReader::new(reader);
+    let format = Format::from(args.value_of("format").unwrap_or("hex").to_string());
+    if args.is_present("source") {
+        let mut input = String::new();
+        reader.read_to_string(&mut input)?;
+        let mut writer = create_writer(output_file.to_string())?;
+        return convert::revert_source(&input, &mut *writer);
+    }
+    let lenient = args.is_present("lenient");
+    let endianness = if args.is_present("little_endian") {
+        Endianness::Little
+    } else {
+        Endianness::Big
+    };
+    let mut lines = LineReader::new(reader, format)
+        .plain(args.is_present("plain_hexdump"))
+        .endianness(endianness)
+        .display_offset(convert::parse_number(
+            args.value_of("display_offset").unwrap_or("0"),
+        )?);
+    if let Some(fmt) = args.value_of("address_format") {
+        lines = lines.address_format(AddressFormat::from(fmt.to_string()));
+    }
+    if let Some(size) = args.value_of("group-size") {
+        lines = lines.group_size(size.parse::<usize>()?);
+    }
+    if let Some(columns) = args.value_of("columns") {
+        lines = lines.columns(columns.parse::<usize>()?);
+    }
+    if let Some(dialect) = args.value_of("from") {
+        lines = lines.dialect(Dialect::from(dialect.to_string()));
+    }
+    let lines = lines.filter(|line| match line {
+        Err(error @ ParseError::Syntax { .. }) if lenient => {
+            report_warning(error);
+            false
+        }
+        _ => true,
+    });
+    match output_file {
+        "stdout" => convert::revert(lines, &mut std::io::stdout()),
+        _ => {
+            // like xxd, an existing output file is patched instead of being truncated
+            let mut writer = OpenOptions::new()
+                .write(true)
+                .create(true)
+                .truncate(false)
+                .open(output_file)?;
+            convert::patch(lines, &mut writer)
+        }
+    }
+}
+
+fn scan<'a>(args: Option<&ArgMatches<'a>>) -> Result<(), anyhow::Error> {
+    let args = args.context("No arguments available")?;
+    let output_file = args.value_of("outfile").unwrap_or("stdout");
+    let input_file = args.value_of("file").unwrap_or("-");
+    let reader = create_reader(input_file.to_string())?;
+    let mut writer = if args.is_present("split") {
+        None
+    } else {
+        Some(create_writer(output_file.to_string())?)
+    };
+    for blob in Scanner::new(BufReader::new(reader)) {
+        let blob = blob?;
+        eprintln!(
+            "lines {}-{}: {} bytes at address {:08X}",
+            blob.first_line,
+            blob.last_line,
+            blob.data.len(),
+            blob.address
+        );
+        match writer.as_mut() {
+            Some(writer) => writer.write_all(&blob.data)?,
+            None => {
+                let path = format!("{}.{}-{}", output_file, blob.first_line, blob.last_line);
+                create_writer(path)?.write_all(&blob.data)?
+            }
+        }
+    }
+    Ok(())
+}
+
+/// Returns the exit status, 0 if the inputs are identical and 1 if they differ.
+fn diff<'a>(args: Option<&ArgMatches<'a>>) -> Result<i32, anyhow::Error> {
+    let args = args.context("No arguments available")?;
+    let output_file = args.value_of("outfile").unwrap_or("stdout");
+    let seek = args.value_of("seek").unwrap_or("0").parse::<usize>()?;
+    let length = match args.value_of("length") {
+        Some(n) => n.parse::<usize>()?,
+        None => usize::MAX,
+    };
+    let files: Vec<&str> = args.values_of("files").into_iter().flatten().collect();
+    let mut inputs = Vec::new();
+    for file in files.iter() {
+        let reader = BufReader::new(create_reader(file.to_string())?);
+        inputs.push(reader.bytes().skip(seek).take(length).flatten());
+    }
+    let context = if args.is_present("all") {
+        None
+    } else {
+        Some(args.value_of("context").unwrap_or("1").parse::<usize>()?)
+    };
+    let mask = match args.value_of("mask") {
+        Some(path) => {
+            let text = std::fs::read_to_string(path)?;
+            diff::parse_mask(&text).with_context(|| format!("invalid mask {}", path))?
+        }
+        None => Vec::new(),
+    };
+    let mut writer = create_writer(output_file.to_string())?;
+    let summary = Diff::new(create_dump_settings(args)?)
+        .context(context)
+        .mask(mask)
+        .compare(inputs, &mut *writer)?;
+    writeln!(
+        writer,
+        "{} differing bytes ({} bits)",
+        summary.differing_bytes, summary.differing_bits
+    )?;
+    if !summary.sizes.windows(2).all(|pair| pair[0] == pair[1]) {
+        for (file, size) in files.iter().zip(&summary.sizes) {
+            writeln!(writer, "{}: {} bytes", file, size)?;
+        }
+    }
+    writer.flush()?;
+    // like cmp and diff, the exit status tells whether the inputs differ
+    Ok(if summary.is_identical() { 0 } else { 1 })
+}
+
 fn report_error<T: Display>(error: &T) {
-    eprintln!("xxd-rs: {}", error);
+    eprintln!("xxd-rs: {:#}", error);
+}
+
+fn report_warning<T: Display>(warning: &T) {
+    eprintln!("xxd-rs: warning: {}", warning);
 }
@@ -0,0 +1,449 @@
+//! The annotate module contains code related to dumping data with labelled regions, e.g. the
+//! fields of a file format.
+use crate::convert::{parse_number, ParseError};
+use crate::dump::{for_each_line, visible_width, Config, OutputLine};
+use serde::de::Error;
+use serde::{Deserialize, Deserializer};
+use serde_json::Value;
+use std::io::Write;
+
+/// Enum which provides the colors of annotated regions.
+#[derive(Debug, PartialEq, Clone, Copy)]
+pub enum Color {
+    Red,
+    Green,
+    Yellow,
+    Blue,
+    Magenta,
+    Cyan,
+}
+
+impl Color {
+    /// Colors which are assigned in turn to annotations without a color.
+    pub const PALETTE: [Color; 6] = [
+        Color::Yellow,
+        Color::Cyan,
+        Color::Magenta,
+        Color::Green,
+        Color::Blue,
+        Color::Red,
+    ];
+
+    fn from_name(name: &str) -> Option<Color> {
+        match name.to_lowercase().as_ref() {
+            "red" => Some(Color::Red),
+            "green" => Some(Color::Green),
+            "yellow" => Some(Color::Yellow),
+            "blue" => Some(Color::Blue),
+            "magenta" => Some(Color::Magenta),
+            "cyan" => Some(Color::Cyan),
+            _ => None,
+        }
+    }
+
+    /// ANSI (SGR) background color code.
+    pub fn ansi_background(&self) -> u8 {
+        match self {
+            Color::Red => 41,
+            Color::Green => 42,
+            Color::Yellow => 43,
+            Color::Blue => 44,
+            Color::Magenta => 45,
+            Color::Cyan => 46,
+        }
+    }
+}
+
+/// Annotation of a single byte of a line (see `OutputLine::annotate`).
+#[derive(Debug, PartialEq, Clone, Copy)]
+pub struct Mark {
+    pub color: Color,
+    /// The byte is the first one of its region.
+    pub opens: bool,
+    /// The byte is the last one of its region.
+    pub closes: bool,
+}
+
+impl Mark {
+    /// Brackets the byte if it is shown by `width` characters, e.g. `[-` for the first byte of a
+    /// region which continues. A single character can't hold both brackets, so it shows a region
+    /// of a single byte as `^`.
+    pub fn bracket(&self, width: usize) -> String {
+        if width == 1 && self.opens && self.closes {
+            return "^".to_string();
+        }
+        let mut bracket = vec!['-'; width];
+        if self.closes {
+            bracket[width - 1] = ']';
+        }
+        if self.opens {
+            bracket[0] = '[';
+        }
+        bracket.into_iter().collect()
+    }
+}
+
+/// A labelled region of the input.
+#[derive(Debug, Clone, PartialEq)]
+pub struct Annotation {
+    pub offset: u64,
+    pub length: u64,
+    pub label: String,
+    pub color: Option<Color>,
+}
+
+impl Annotation {
+    fn contains(&self, address: u64) -> bool {
+        address >= self.offset && address - self.offset < self.length
+    }
+}
+
+/// Parses annotations, either CSV or a JSON array of objects.
+///
+/// Every CSV line contains `offset,length,label[,color]`, an optional header line is skipped and
+/// everything following a `#` at the start of a line is a comment. JSON objects contain the
+/// fields `offset`, `length`, `label` and optionally `color`. Numbers are decimal or hex prefixed
+/// with `0x` (as JSON string), colors are `red`, `green`, `yellow`, `blue`, `magenta` or `cyan`.
+pub fn parse_annotations(text: &str) -> Result<Vec<Annotation>, ParseError> {
+    if text.trim_start().starts_with('[') {
+        parse_json(text)
+    } else {
+        parse_csv(text)
+    }
+}
+
+fn parse_csv(text: &str) -> Result<Vec<Annotation>, ParseError> {
+    let mut annotations = Vec::new();
+    for (index, line) in text.lines().enumerate() {
+        let entry = line.trim();
+        if entry.is_empty() || entry.starts_with('#') {
+            continue;
+        }
+        let error = |message: String| ParseError::Syntax {
+            line: index + 1,
+            column: line.find(|c: char| !c.is_whitespace()).unwrap_or(0) + 1,
+            message,
+        };
+        let fields =
+            csv_fields(entry).ok_or_else(|| error(format!("unterminated quote in '{}'", entry)))?;
+        if annotations.is_empty() && fields[0].eq_ignore_ascii_case("offset") {
+            continue;
+        }
+        if fields.len() < 3 || fields.len() > 4 {
+            return Err(error(format!(
+                "expected offset,length,label[,color], found '{}'",
+                entry
+            )));
+        }
+        let field = |index: usize, name: &str| {
+            parse_number(&fields[index])
+                .map_err(|_| error(format!("invalid {} '{}'", name, fields[index])))
+        };
+        let color = match fields.get(3).map(|color| color.trim()) {
+            None | Some("") => None,
+            Some(name) => Some(
+                Color::from_name(name).ok_or_else(|| error(format!("unknown color '{}'", name)))?,
+            ),
+        };
+        annotations.push(Annotation {
+            offset: field(0, "offset")?,
+            length: field(1, "length")?,
+            label: fields[2].trim().to_string(),
+            color,
+        });
+    }
+    Ok(annotations)
+}
+
+/// Splits a CSV line into its fields, quoted fields may contain commas and doubled quotes
+/// (RFC 4180). Returns `None` if a quote isn't terminated.
+fn csv_fields(line: &str) -> Option<Vec<String>> {
+    let mut fields = Vec::new();
+    let mut field = String::new();
+    let mut quoted = false;
+    let mut characters = line.chars().peekable();
+    while let Some(character) = characters.next() {
+        match character {
+            '"' if quoted && characters.peek() == Some(&'"') => {
+                field.push('"');
+                characters.next();
+            }
+            '"' if quoted => quoted = false,
+            '"' if field.trim().is_empty() => {
+                field.clear();
+                quoted = true;
+            }
+            ',' if !quoted => fields.push(std::mem::take(&mut field)),
+            _ => field.push(character),
+        }
+    }
+    if quoted {
+        return None;
+    }
+    fields.push(field);
+    Some(fields)
+}
+
+/// An annotation in a JSON array.
+#[derive(Deserialize)]
+struct JsonAnnotation {
+    #[serde(deserialize_with = "json_number")]
+    offset: u64,
+    #[serde(deserialize_with = "json_number")]
+    length: u64,
+    label: String,
+    #[serde(default, deserialize_with = "json_color")]
+    color: Option<Color>,
+}
+
+/// A non-negative integer, or a string containing a decimal or hex number (see `parse_number`).
+fn json_number<'de, D: Deserializer<'de>>(deserializer: D) -> Result<u64, D::Error> {
+    match Value::deserialize(deserializer)? {
+        Value::Number(number) => number
+            .as_u64()
+            .ok_or_else(|| D::Error::custom(format!("invalid number {}", number))),
+        Value::String(text) => parse_number(&text).map_err(D::Error::custom),
+        _ => Err(D::Error::custom("expected a number")),
+    }
+}
+
+fn json_color<'de, D: Deserializer<'de>>(deserializer: D) -> Result<Option<Color>, D::Error> {
+    match Option::<String>::deserialize(deserializer)? {
+        Some(name) => Color::from_name(&name)
+            .map(Some)
+            .ok_or_else(|| D::Error::custom(format!("unknown color '{}'", name))),
+        None => Ok(None),
+    }
+}
+
+fn parse_json(text: &str) -> Result<Vec<Annotation>, ParseError> {
+    let annotations: Vec<JsonAnnotation> = serde_json::from_str(text).map_err(|error| {
+        // the position is part of the message as well
+        let position = format!(" at line {} column {}", error.line(), error.column());
+        let message = error.to_string();
+        ParseError::Syntax {
+            line: error.line(),
+            column: error.column(),
+            message: message.trim_end_matches(&position).to_string(),
+        }
+    })?;
+    Ok(annotations
+        .into_iter()
+        .map(|annotation| Annotation {
+            offset: annotation.offset,
+            length: annotation.length,
+            label: annotation.label,
+            color: annotation.color,
+        })
+        .collect())
+}
+
+/// The `Annotator` struct contains all settings needed to dump data with annotated regions.
+#[derive(Debug, Clone)]
+pub struct Annotator {
+    output_settings: Config,
+    annotations: Vec<Annotation>,
+    colors: Vec<Color>,
+}
+
+impl Annotator {
+    /// Annotations without a color get one of the `Color::PALETTE` in turn.
+    pub fn new(output_settings: Config, annotations: Vec<Annotation>) -> Annotator {
+        let mut palette = Color::PALETTE.iter().cycle();
+        let mut annotations: Vec<(Annotation, Color)> = annotations
+            .into_iter()
+            .map(|annotation| {
+                let color = match annotation.color {
+                    Some(color) => color,
+                    None => *palette.next().unwrap(),
+                };
+                (annotation, color)
+            })
+            .collect();
+        // nested regions follow the region containing them, so they take precedence
+        annotations.sort_by_key(|(annotation, _)| annotation.offset);
+        let (annotations, colors) = annotations.into_iter().unzip();
+        Annotator {
+            output_settings,
+            annotations,
+            colors,
+        }
+    }
+
+    /// Dumps `sequence` with the annotated bytes colored, or bracketed in a line below if colors
+    /// are disabled. Every line is followed by a `#` comment listing the labels of the regions
+    /// starting on it.
+    pub fn dump<I>(&self, sequence: I, writer: &mut dyn Write) -> Result<(), anyhow::Error>
+    where
+        I: Iterator<Item = u8>,
+    {
+        let settings = self.output_settings;
+        for_each_line(sequence, settings, |chunk| {
+            let address = settings.start_address() + chunk.offset;
+            let marks = self.marks(address, chunk.data.len());
+            let line_settings = settings.set_address(address);
+            let line = OutputLine::new(chunk.data)
+                .format(line_settings)
+                .context(chunk.before, chunk.after)
+                .annotate(&marks);
+            let labels: Vec<&str> = self
+                .annotations
+                .iter()
+                .filter(|annotation| {
+                    annotation.offset >= address
+                        && annotation.offset - address < chunk.data.len() as u64
+                })
+                .map(|annotation| annotation.label.as_ref())
+                .collect();
+            if labels.is_empty() {
+                writeln!(writer, "{}", line)?;
+            } else {
+                // the comments of a shorter last line line up with the ones above
+                let line = line.to_string();
+                let padding = line_settings
+                    .line_width()
+                    .saturating_sub(visible_width(&line));
+                let labels = labels.join(", ");
+                writeln!(writer, "{}{:2$}  # {3}", line, "", padding, labels)?;
+            }
+            if !settings.uses_color() && marks.iter().any(Option::is_some) {
+                let brackets = OutputLine::new(chunk.data)
+                    .format(line_settings)
+                    .annotate(&marks)
+                    .markers();
+                writeln!(writer, "{}", brackets.to_string().trim_end())?;
+            }
+            Ok(())
+        })
+    }
+
+    /// Determines the marks of the `length` bytes at `address`, the last region containing a
+    /// byte wins.
+    fn marks(&self, address: u64, length: usize) -> Vec<Option<Mark>> {
+        (address..address + length as u64)
+            .map(|address| {
+                self.annotations
+                    .iter()
+                    .zip(&self.colors)
+                    .rev()
+                    .find(|(annotation, _)| annotation.contains(address))
+                    .map(|(annotation, color)| Mark {
+                        color: *color,
+                        opens: address == annotation.offset,
+                        closes: address - annotation.offset + 1 == annotation.length,
+                    })
+            })
+            .collect()
+    }
+}
+
+#[cfg(test)]
+mod test {
+
+    use super::*;
+    use crate::dump::ControlStyle;
+
+    fn annotate(annotator: Annotator, data: &[u8]) -> String {
+        let mut output = Vec::new();
+        annotator.dump(data.iter().cloned(), &mut output).unwrap();
+        String::from_utf8(output).unwrap()
+    }
+
+    #[test]
+    fn parse_csv_annotations() {
+        let text =
+            "offset,length,label,color\n# ELF header\n0,4,magic,red\n0x4,1,\"class, 64 bit\"\n";
+        let annotations = parse_annotations(text).unwrap();
+        assert_eq!(
+            vec![
+                Annotation {
+                    offset: 0,
+                    length: 4,
+                    label: "magic".to_string(),
+                    color: Some(Color::Red),
+                },
+                Annotation {
+                    offset: 4,
+                    length: 1,
+                    label: "class, 64 bit".to_string(),
+                    color: None,
+                },
+            ],
+            annotations
+        );
+        let error = parse_annotations("0,4,magic\n  4,x,class").unwrap_err();
+        assert_eq!("line 2, column 3: invalid length 'x'", error.to_string());
+        assert!(parse_annotations("0,4,magic,purple").is_err());
+    }
+
+    #[test]
+    fn parse_json_annotations() {
+        let text = r#"[
+            {"offset": 0, "length": 4, "label": "magic \"ELF\"", "color": "red"},
+            {"offset": "0x4", "length": 1, "label": "class"}
+        ]"#;
+        let annotations = parse_annotations(text).unwrap();
+        assert_eq!(2, annotations.len());
+        assert_eq!("magic \"ELF\"", annotations[0].label);
+        assert_eq!(Some(Color::Red), annotations[0].color);
+        assert_eq!(4, annotations[1].offset);
+        assert_eq!(None, annotations[1].color);
+        let error = parse_annotations("[\n  {\"offset\": 1, \"label\": \"a\"}\n]").unwrap_err();
+        assert_eq!(
+            "line 2, column 29: missing field `length`",
+            error.to_string()
+        );
+        let error = parse_annotations(r#"[{"offset": "0x", "length": 1, "label": "a"}]"#);
+        assert_eq!(
+            "line 1, column 16: invalid number '0x'",
+            error.unwrap_err().to_string()
+        );
+        let text = r#"[{"offset": 0, "length": 1, "label": "a", "color": "purple"}]"#;
+        let error = parse_annotations(text).unwrap_err();
+        assert!(error.to_string().contains("unknown color 'purple'"));
+        assert!(parse_annotations("[{\"offset\": 1.5}]").is_err());
+        assert!(parse_annotations("[] trailing").is_err());
+    }
+
+    #[test]
+    fn bracketed_regions_and_labels() {
+        let annotations = parse_annotations("0,4,magic\n4,1,class\n6,4,version").unwrap();
+        let annotator = Annotator::new(Config::new().columns(4).group_size(2), annotations);
+        let output = annotate(annotator, b"\x7fELF\x02\x01\x01\x00\x00\x00");
+        let expected_output = "00000000: 7F45 4C46 0201 0100  .ELF....  # magic, class, version\n\
+                               \x20         [--- ---] []   [---  [--]^ [-\n\
+                               00000008: 0000                 ..\n\
+                               \x20         ---]                 -]\n";
+        assert_eq!(expected_output, output);
+    }
+
+    #[test]
+    fn colored_regions() {
+        let annotations = parse_annotations("1,1,b,blue").unwrap();
+        let annotator = Annotator::new(
+            Config::new()
+                .columns(4)
+                .color(true)
+                .show_interpretation(false),
+            annotations,
+        );
+        let output = annotate(annotator, b"ab");
+        assert!(output.contains("\x1b[36m61\x1b[0m \x1b[30;44m62\x1b[0m"));
+        assert!(output.ends_with("  # b\n"));
+        assert_eq!(1, output.lines().count());
+    }
+
+    #[test]
+    fn labels_line_up_after_a_shorter_line() {
+        let annotations = parse_annotations("0,1,first\n4,1,last").unwrap();
+        let config = Config::new().columns(4).show_interpretation(false);
+        let output = annotate(Annotator::new(config, annotations.clone()), b"\x01\x02abc");
+        let lines: Vec<&str> = output.lines().step_by(2).collect();
+        assert_eq!(lines[0].find('#'), lines[1].find('#'));
+        let config = Config::new().columns(4).control_style(ControlStyle::Caret);
+        let output = annotate(Annotator::new(config, annotations), b"\x01\x02abc");
+        let lines: Vec<&str> = output.lines().step_by(2).collect();
+        assert_eq!("00000000: 01 02 61 62  ^A^Bab    # first", lines[0]);
+        assert_eq!("00000004: 63           c         # last", lines[1]);
+    }
+}
@@ -0,0 +1,311 @@
+//! The codepage module contains the character maps used for the interpretation column of a dump.
+use std::convert::From;
+
+/// Character shown for bytes which have no printable character in a code page.
+pub const PLACEHOLDER: char = '.';
+
+/// Character shown for the remaining bytes of a character encoded in multiple bytes.
+pub const CONTINUATION: char = '•';
+
+/// Character shown for bytes which aren't part of a valid UTF-8/UTF-16 sequence.
+pub const INVALID: char = '�';
+
+/// Enum which provides all code pages the interpretation column can be shown in.
+#[derive(Debug, PartialEq, Clone, Copy)]
+pub enum CodePage {
+    Ascii,
+    Latin1,
+    Ebcdic,
+    Cp437,
+    Utf8,
+    Utf16Le,
+    Utf16Be,
+}
+
+impl From<String> for CodePage {
+    fn from(code_page: String) -> Self {
+        match code_page.as_ref() {
+            "ascii" => CodePage::Ascii,
+            "latin1" => CodePage::Latin1,
+            "ebcdic" => CodePage::Ebcdic,
+            "cp437" => CodePage::Cp437,
+            "utf8" => CodePage::Utf8,
+            "utf16le" => CodePage::Utf16Le,
+            "utf16be" => CodePage::Utf16Be,
+            _ => panic!("Invalid code page"),
+        }
+    }
+}
+
+impl CodePage {
+    /// Character which represents `byte`, invisible characters and unassigned bytes are shown as
+    /// `PLACEHOLDER`.
+    ///
+    /// Control characters are returned as their ASCII control character (`0x00..=0x1f`, `0x7f`),
+    /// so they can be shown in any `ControlStyle` of the dump.
+    pub fn character(&self, byte: u8) -> char {
+        match (self, byte) {
+            (CodePage::Ebcdic, 0x40..=0xff) => EBCDIC[byte as usize - 0x40],
+            (CodePage::Ebcdic, _) => ebcdic_control(byte),
+            (_, 0x20..=0x7e) => byte as char,
+            // no-break space and soft hyphen would break the alignment of the column
+            (CodePage::Latin1, 0xa0) | (CodePage::Latin1, 0xad) => PLACEHOLDER,
+            (CodePage::Latin1, 0xa1..=0xff) => byte as char,
+            (CodePage::Cp437, 0x7f) => '⌂',
+            (CodePage::Cp437, 0x80..=0xff) => CP437[byte as usize - 0x80],
+            (_, 0x00..=0x1f) | (_, 0x7f) => byte as char,
+            _ => PLACEHOLDER,
+        }
+    }
+
+    /// Amount of bytes following a line which are needed to interpret its last bytes.
+    pub fn lookahead(&self) -> usize {
+        match self {
+            CodePage::Utf8 | CodePage::Utf16Le | CodePage::Utf16Be => 3,
+            _ => 0,
+        }
+    }
+
+    /// Interprets `data` as one character per byte.
+    ///
+    /// A character encoded in multiple bytes is shown in the column of its first byte, followed by
+    /// `CONTINUATION` for its remaining bytes (one less for double width characters). `before` and
+    /// `after` are the bytes surrounding `data` in the input, they are needed to interpret
+    /// characters crossing the boundaries of `data`. For UTF-16 `before` must start at the first
+    /// byte of a code unit.
+    pub fn interpret(&self, before: &[u8], data: &[u8], after: &[u8]) -> Vec<char> {
+        let decode: fn(&[u8]) -> (usize, Option<char>) = match self {
+            CodePage::Utf8 => utf8_sequence,
+            CodePage::Utf16Le => |bytes| utf16_sequence(bytes, u16::from_le_bytes),
+            CodePage::Utf16Be => |bytes| utf16_sequence(bytes, u16::from_be_bytes),
+            _ => return data.iter().map(|b| self.character(*b)).collect(),
+        };
+        let input: Vec<u8> = before.iter().chain(data).chain(after).cloned().collect();
+        let data_range = before.len()..before.len() + data.len();
+        let mut characters = Vec::with_capacity(data.len());
+        let mut position = 0;
+        while position < data_range.end {
+            let (length, character) = decode(&input[position..]);
+            let mut continuations = (position + 1..position + length)
+                .filter(|index| data_range.contains(index))
+                .count();
+            if data_range.contains(&position) {
+                let character = character.map_or(INVALID, printable);
+                if is_wide(character) && continuations > 0 {
+                    continuations -= 1;
+                }
+                characters.push(character);
+            }
+            characters.extend(std::iter::repeat_n(CONTINUATION, continuations));
+            position += length;
+        }
+        characters
+    }
+}
+
+/// ASCII control character of an EBCDIC control byte, the remaining ones are shown as
+/// `PLACEHOLDER`.
+fn ebcdic_control(byte: u8) -> char {
+    let control = match byte {
+        0x00..=0x03 | 0x0b..=0x13 | 0x18 | 0x19 | 0x1c..=0x1f => byte,
+        0x05 => 0x09,
+        0x07 => 0x7f,
+        0x16 => 0x08,
+        0x25 => 0x0a,
+        0x26 => 0x17,
+        0x27 => 0x1b,
+        0x2d => 0x05,
+        0x2e => 0x06,
+        0x2f => 0x07,
+        0x32 => 0x16,
+        0x37 => 0x04,
+        0x3c => 0x14,
+        0x3d => 0x15,
+        0x3f => 0x1a,
+        _ => return PLACEHOLDER,
+    };
+    control as char
+}
+
+/// Decodes the UTF-8 sequence `bytes` start with, an invalid sequence is skipped byte by byte.
+fn utf8_sequence(bytes: &[u8]) -> (usize, Option<char>) {
+    let length = match bytes[0] {
+        0x00..=0x7f => 1,
+        0xc2..=0xdf => 2,
+        0xe0..=0xef => 3,
+        0xf0..=0xf4 => 4,
+        _ => return (1, None),
+    };
+    match bytes.get(..length).map(std::str::from_utf8) {
+        Some(Ok(sequence)) => (length, sequence.chars().next()),
+        _ => (1, None),
+    }
+}
+
+/// Decodes the UTF-16 code unit or surrogate pair `bytes` start with.
+fn utf16_sequence(bytes: &[u8], unit: fn([u8; 2]) -> u16) -> (usize, Option<char>) {
+    if bytes.len() < 2 {
+        return (bytes.len(), None);
+    }
+    let units = bytes
+        .chunks_exact(2)
+        .take(2)
+        .map(|pair| unit([pair[0], pair[1]]));
+    match std::char::decode_utf16(units).next() {
+        Some(Ok(character)) => (character.len_utf16() * 2, Some(character)),
+        _ => (2, None),
+    }
+}
+
+/// Replaces characters which would break the alignment of the interpretation column, ASCII
+/// control characters are kept (see `CodePage::character`).
+fn printable(character: char) -> char {
+    match character {
+        '\u{80}'..='\u{9f}' => PLACEHOLDER,
+        // no-break space, soft hyphen, zero width and combining characters
+        '\u{a0}' | '\u{ad}' => PLACEHOLDER,
+        '\u{300}'..='\u{36f}' | '\u{200b}'..='\u{200f}' | '\u{2028}'..='\u{202e}' => PLACEHOLDER,
+        '\u{20d0}'..='\u{20ff}' | '\u{fe00}'..='\u{fe0f}' | '\u{feff}' => PLACEHOLDER,
+        _ => character,
+    }
+}
+
+/// Characters which occupy two columns in a terminal (East Asian wide characters and emoji).
+pub(crate) fn is_wide(character: char) -> bool {
+    matches!(character,
+        '\u{1100}'..='\u{115f}'
+        | '\u{2e80}'..='\u{303e}'
+        | '\u{3041}'..='\u{a4cf}'
+        | '\u{ac00}'..='\u{d7a3}'
+        | '\u{f900}'..='\u{faff}'
+        | '\u{fe30}'..='\u{fe4f}'
+        | '\u{ff00}'..='\u{ff60}'
+        | '\u{ffe0}'..='\u{ffe6}'
+        | '\u{1f300}'..='\u{1f64f}'
+        | '\u{1f900}'..='\u{1f9ff}'
+        | '\u{20000}'..='\u{3fffd}')
+}
+
+/// EBCDIC code page 037 (US/Canada) starting at 0x40, the bytes below are control characters.
+/// Invisible characters are mapped to `PLACEHOLDER`.
+const EBCDIC: [char; 192] = [
+    ' ', '.', 'â', 'ä', 'à', 'á', 'ã', 'å', 'ç', 'ñ', '¢', '.', '<', '(', '+', '|', '&', 'é', 'ê',
+    'ë', 'è', 'í', 'î', 'ï', 'ì', 'ß', '!', '$', '*', ')', ';', '¬', '-', '/', 'Â', 'Ä', 'À', 'Á',
+    'Ã', 'Å', 'Ç', 'Ñ', '¦', ',', '%', '_', '>', '?', 'ø', 'É', 'Ê', 'Ë', 'È', 'Í', 'Î', 'Ï', 'Ì',
+    '`', ':', '#', '@', '\'', '=', '"', 'Ø', 'a', 'b', 'c', 'd', 'e', 'f', 'g', 'h', 'i', '«', '»',
+    'ð', 'ý', 'þ', '±', '°', 'j', 'k', 'l', 'm', 'n', 'o', 'p', 'q', 'r', 'ª', 'º', 'æ', '¸', 'Æ',
+    '¤', 'µ', '~', 's', 't', 'u', 'v', 'w', 'x', 'y', 'z', '¡', '¿', 'Ð', 'Ý', 'Þ', '®', '^', '£',
+    '¥', '·', '©', '§', '¶', '¼', '½', '¾', '[', ']', '¯', '¨', '´', '×', '{', 'A', 'B', 'C', 'D',
+    'E', 'F', 'G', 'H', 'I', '.', 'ô', 'ö', 'ò', 'ó', 'õ', '}', 'J', 'K', 'L', 'M', 'N', 'O', 'P',
+    'Q', 'R', '¹', 'û', 'ü', 'ù', 'ú', 'ÿ', '\\', '÷', 'S', 'T', 'U', 'V', 'W', 'X', 'Y', 'Z', '²',
+    'Ô', 'Ö', 'Ò', 'Ó', 'Õ', '0', '1', '2', '3', '4', '5', '6', '7', '8', '9', '³', 'Û', 'Ü', 'Ù',
+    'Ú', '.',
+];
+
+/// Upper half of IBM PC code page 437, the lower half matches ASCII.
+const CP437: [char; 128] = [
+    'Ç', 'ü', 'é', 'â', 'ä', 'à', 'å', 'ç', 'ê', 'ë', 'è', 'ï', 'î', 'ì', 'Ä', 'Å', 'É', 'æ', 'Æ',
+    'ô', 'ö', 'ò', 'û', 'ù', 'ÿ', 'Ö', 'Ü', '¢', '£', '¥', '₧', 'ƒ', 'á', 'í', 'ó', 'ú', 'ñ', 'Ñ',
+    'ª', 'º', '¿', '⌐', '¬', '½', '¼', '¡', '«', '»', '░', '▒', '▓', '│', '┤', '╡', '╢', '╖', '╕',
+    '╣', '║', '╗', '╝', '╜', '╛', '┐', '└', '┴', '┬', '├', '─', '┼', '╞', '╟', '╚', '╔', '╩', '╦',
+    '╠', '═', '╬', '╧', '╨', '╤', '╥', '╙', '╘', '╒', '╓', '╫', '╪', '┘', '┌', '█', '▄', '▌', '▐',
+    '▀', 'α', 'ß', 'Γ', 'π', 'Σ', 'σ', 'µ', 'τ', 'Φ', 'Θ', 'Ω', 'δ', '∞', 'φ', 'ε', '∩', '≡', '±',
+    '≥', '≤', '⌠', '⌡', '÷', '≈', '°', '∙', '·', '√', 'ⁿ', '²', '■', '.',
+];
+
+#[cfg(test)]
+mod test {
+
+    use super::*;
+
+    #[test]
+    fn ascii_maps_printable_characters_only() {
+        assert_eq!('A', CodePage::Ascii.character(0x41));
+        assert_eq!(' ', CodePage::Ascii.character(0x20));
+        assert_eq!('\u{14}', CodePage::Ascii.character(0x14));
+        assert_eq!('\u{7f}', CodePage::Ascii.character(0x7f));
+        assert_eq!(PLACEHOLDER, CodePage::Ascii.character(0xe4));
+    }
+
+    #[test]
+    fn latin1_maps_upper_half() {
+        assert_eq!('ä', CodePage::Latin1.character(0xe4));
+        assert_eq!('ÿ', CodePage::Latin1.character(0xff));
+        assert_eq!(PLACEHOLDER, CodePage::Latin1.character(0x85));
+        assert_eq!(PLACEHOLDER, CodePage::Latin1.character(0xa0));
+    }
+
+    #[test]
+    fn ebcdic_maps_letters_and_digits() {
+        let text: String = b"\xc8\x85\x93\x93\x96\x40\xf1\xf2\x5a"
+            .iter()
+            .map(|b| CodePage::Ebcdic.character(*b))
+            .collect();
+        assert_eq!("Hello 12!", text);
+        assert_eq!('\n', CodePage::Ebcdic.character(0x25));
+        assert_eq!(PLACEHOLDER, CodePage::Ebcdic.character(0x20));
+        assert_eq!(PLACEHOLDER, CodePage::Ebcdic.character(0xff));
+    }
+
+    #[test]
+    fn cp437_maps_box_drawing_characters() {
+        assert_eq!('╔', CodePage::Cp437.character(0xc9));
+        assert_eq!('é', CodePage::Cp437.character(0x82));
+        assert_eq!('⌂', CodePage::Cp437.character(0x7f));
+        assert_eq!('\n', CodePage::Cp437.character(0x0a));
+    }
+
+    fn interpret(code_page: CodePage, before: &[u8], data: &[u8], after: &[u8]) -> String {
+        code_page
+            .interpret(before, data, after)
+            .into_iter()
+            .collect()
+    }
+
+    #[test]
+    fn utf8_shows_characters_at_their_first_byte() {
+        assert_eq!(
+            "aä•€••",
+            interpret(CodePage::Utf8, &[], "aä€".as_bytes(), &[])
+        );
+        assert_eq!("日•", interpret(CodePage::Utf8, &[], "日".as_bytes(), &[]));
+        assert_eq!(
+            "\0\n.•",
+            interpret(CodePage::Utf8, &[], b"\x00\n\xc2\x85", &[])
+        );
+    }
+
+    #[test]
+    fn utf8_sequences_crossing_the_boundaries() {
+        let text = "x€y".as_bytes();
+        assert_eq!("x€", interpret(CodePage::Utf8, &[], &text[..2], &text[2..]));
+        assert_eq!(
+            "••y",
+            interpret(CodePage::Utf8, &text[..2], &text[2..], &[])
+        );
+    }
+
+    #[test]
+    fn utf8_flags_invalid_sequences() {
+        assert_eq!("��a", interpret(CodePage::Utf8, &[], b"\x80\xffa", &[]));
+        assert_eq!("�a", interpret(CodePage::Utf8, &[], b"\xe2a", &[]));
+        assert_eq!("�", interpret(CodePage::Utf8, &[], b"\xe2", &[]));
+    }
+
+    #[test]
+    fn utf16_shows_characters_at_their_first_byte() {
+        let le: Vec<u8> = "a€😀".encode_utf16().flat_map(u16::to_le_bytes).collect();
+        assert_eq!("a•€•😀••", interpret(CodePage::Utf16Le, &[], &le, &[]));
+        let be: Vec<u8> = "a€".encode_utf16().flat_map(u16::to_be_bytes).collect();
+        assert_eq!("a•€•", interpret(CodePage::Utf16Be, &[], &be, &[]));
+        assert_eq!("a•€", interpret(CodePage::Utf16Be, &[], &be[..3], &be[3..]));
+        assert_eq!(
+            "•€",
+            interpret(CodePage::Utf16Le, &le[..1], &le[1..3], &le[3..])
+        );
+        assert_eq!(
+            "�•",
+            interpret(CodePage::Utf16Le, &[], &[0x00, 0xd8], &[0x41, 0x00])
+        );
+    }
+}
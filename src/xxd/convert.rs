@@ -1,103 +1,1549 @@
+//! The convert module contains code related to turning dumps back into binary data.
+use crate::dump::{AddressFormat, Endianness, Format, RULER_MARKER};
 use nom::{
-    call, do_parse, error_position, flat_map, many0, map_res, named, sep, take, take_until,
-    take_until_and_consume, wrap_sep, ws,
+    call, do_parse, error_position, many0, map_res, sep, tag, take, take_until, wrap_sep, ws,
+    IResult,
 };
+use std::fmt;
+use std::io::{self, BufRead, Read, Seek, SeekFrom, Write};
 use std::num::ParseIntError;
-use std::str;
 
-fn hex_string_to_u8(s: &str) -> Result<u8, ParseIntError> {
-    u8::from_str_radix(s, 16)
+fn string_to_u8(s: &str, fmt: Format) -> Result<u8, ParseIntError> {
+    u8::from_str_radix(s, fmt.radix())
 }
 
-named!(address<&str, u64>,
+fn hex_string_to_u64(s: &str) -> Result<u64, ParseIntError> {
+    u64::from_str_radix(s, 16)
+}
+
+/// Parses a decimal number or a hex number prefixed with `0x`.
+pub fn parse_number(text: &str) -> Result<u64, anyhow::Error> {
+    let text = text.trim();
+    let number = match text.strip_prefix("0x").or_else(|| text.strip_prefix("0X")) {
+        Some(hex) => u64::from_str_radix(hex, 16),
+        None => text.parse::<u64>(),
+    };
+    number.map_err(|_| anyhow::anyhow!("invalid number '{}'", text))
+}
+
+/// Format of the address column of a dump (see `Config::address_format` and
+/// `Config::display_offset`).
+#[derive(Debug, Clone, Copy)]
+struct Addressing {
+    radix: u32,
+    display_offset: u64,
+}
+
+impl Default for Addressing {
+    fn default() -> Self {
+        Addressing {
+            radix: 16,
+            display_offset: 0,
+        }
+    }
+}
+
+/// Parses the address of a line, which may be preceded by the file offset (see
+/// `Config::show_file_offset`). Returns the file offset, if present, and the address.
+fn line_address(s: &str, radix: u32) -> Result<(Option<u64>, u64), ParseIntError> {
+    match s.split_once(' ') {
+        Some((file_offset, address)) => Ok((
+            Some(u64::from_str_radix(file_offset, radix)?),
+            u64::from_str_radix(address, radix)?,
+        )),
+        None => Ok((None, u64::from_str_radix(s, radix)?)),
+    }
+}
+
+fn address(input: &str, radix: u32) -> IResult<&str, (Option<u64>, u64)> {
     do_parse!(
-        adr : map_res!(
-                take_until!(":"),
-                str::FromStr::from_str
-            ) >>
-        take!(1) >>
-        (adr)
+        input,
+        adr: map_res!(take_until!(":"), |s| line_address(s, radix)) >> tag!(":") >> (adr)
     )
-);
+}
+
+/// Splits the address column off a line and returns the rest of the line and the position of
+/// its data, which is `offset` if the line has no address.
+///
+/// The file offset is the position of the data if it is shown, otherwise the display offset is
+/// subtracted from the address.
+fn line_position(
+    input: &str,
+    offset: u64,
+    addressing: Addressing,
+) -> Result<(&str, u64), (usize, String)> {
+    match address(input, addressing.radix) {
+        IResult::Done(rest, (Some(file_offset), _)) => Ok((rest, file_offset)),
+        IResult::Done(rest, (None, address)) => {
+            match address.checked_sub(addressing.display_offset) {
+                Some(position) => Ok((rest, position)),
+                None => Err((
+                    1,
+                    format!(
+                        "address lies before the display offset {:X}",
+                        addressing.display_offset
+                    ),
+                )),
+            }
+        }
+        _ => Ok((input, offset)),
+    }
+}
 
-named!(pub bytes<&str, Vec<u8> >,
+/// Parses whitespace separated values formatted with `fmt` into bytes.
+pub fn bytes(input: &str, fmt: Format) -> IResult<&str, Vec<u8>> {
     many0!(
-        ws!(
-            map_res!(
-                take!(2),
-                hex_string_to_u8
-            )
-        )
+        input,
+        ws!(map_res!(take!(fmt.width()), |s| string_to_u8(s, fmt)))
     )
-);
+}
 
+/// A single line of a dump, i.e. the address of its first byte and the data it contains.
 #[derive(Debug, PartialEq)]
-struct Line {
-    address: u64,
-    data: Vec<u8>,
+pub struct Line {
+    pub address: u64,
+    pub data: Vec<u8>,
 }
 
-named!(hexdum_line<&str, Line>,
-    do_parse!(
-        adr: address >>
-        data: flat_map!(take_until_and_consume!("  "), bytes) >>
-        take_until_and_consume!("\n") >>
-        (Line { address: adr, data } )
-    )
-);
+/// Error which occurs while reading a dump.
+#[derive(Debug)]
+pub enum ParseError {
+    Io(io::Error),
+    Syntax {
+        line: usize,
+        column: usize,
+        message: String,
+    },
+}
+
+impl fmt::Display for ParseError {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match self {
+            ParseError::Io(error) => write!(f, "{}", error),
+            ParseError::Syntax {
+                line,
+                column,
+                message,
+            } => write!(f, "line {}, column {}: {}", line, column, message),
+        }
+    }
+}
+
+impl std::error::Error for ParseError {}
+
+impl From<io::Error> for ParseError {
+    fn from(error: io::Error) -> Self {
+        ParseError::Io(error)
+    }
+}
+
+fn digit_name(fmt: Format) -> &'static str {
+    match fmt.radix() {
+        16 => "hex digit",
+        10 => "decimal digit",
+        8 => "octal digit",
+        _ => "binary digit",
+    }
+}
+
+/// Returns the (1 based) column of the byte offset `index` within `text`.
+fn column(text: &str, index: usize) -> usize {
+    text[..index].chars().count() + 1
+}
+
+/// Explains why `values` can't be parsed with the format `fmt`.
+///
+/// Returns the byte offset of the offending character within `values` and a message.
+fn diagnose_values(values: &str, fmt: Format) -> (usize, String) {
+    let mut value = String::new();
+    let mut value_end = 0;
+    for (index, c) in values.char_indices() {
+        if c.is_whitespace() {
+            if !value.is_empty() {
+                break;
+            }
+            continue;
+        }
+        if !c.is_digit(fmt.radix()) {
+            return (
+                index,
+                format!("expected {}, found '{}'", digit_name(fmt), c),
+            );
+        }
+        value.push(c);
+        value_end = index + c.len_utf8();
+        if value.len() == fmt.width() {
+            if string_to_u8(&value, fmt).is_err() {
+                let start = value_end - value.len();
+                return (start, format!("value '{}' does not fit into a byte", value));
+            }
+            value.clear();
+        }
+    }
+    let message = format!(
+        "expected {} digits per value, found '{}'",
+        fmt.width(),
+        value
+    );
+    (value_end - value.len(), message)
+}
+
+/// Parses a single line of a dump created by the dump module using the output format `fmt`.
+///
+/// The address column is optional, if it is missing `offset` is used as address of the line.
+/// The values end at the first double space, everything after it is the interpretation column.
+/// On error the (1 based) column and a description of the problem are returned.
+fn hexdum_line(
+    input: &str,
+    offset: u64,
+    fmt: Format,
+    addressing: Addressing,
+) -> Result<Line, (usize, String)> {
+    let (rest, address) = line_position(input, offset, addressing)?;
+    let start = input.len() - rest.trim_start().len();
+    let values = match input[start..].find("  ") {
+        Some(end) => &input[start..start + end],
+        None => &input[start..],
+    };
+    match bytes(values, fmt) {
+        IResult::Done(rest, data) if rest.trim().is_empty() => Ok(Line { address, data }),
+        _ => {
+            let (index, message) = diagnose_values(values, fmt);
+            Err((column(input, start + index), message))
+        }
+    }
+}
+
+/// Parses a single line of a little-endian dump (see `Config::endianness`), the bytes of every
+/// group are reversed.
+///
+/// The groups are located by their columns: a line holds up to `columns` groups of `group_size`
+/// bytes, each one followed by a space. An incomplete group is aligned to the right like the
+/// least significant bytes of a complete one and ends the line.
+fn little_endian_line(
+    input: &str,
+    offset: u64,
+    fmt: Format,
+    addressing: Addressing,
+    (group_size, columns): (usize, usize),
+) -> Result<Line, (usize, String)> {
+    let (rest, address) = line_position(input, offset, addressing)?;
+    // a space separates the address from the first group
+    let start = match input.len() - rest.len() {
+        0 => 0,
+        end => end + 1,
+    };
+    let width = group_size * fmt.width();
+    let mut data = Vec::new();
+    for index in 0..columns {
+        let group_start = start + index * (width + 1);
+        let group_end = std::cmp::min(group_start + width, input.len());
+        let field = match input.get(group_start..group_end) {
+            Some(field) => field,
+            None => break,
+        };
+        let group = field.trim_start();
+        if group.is_empty() {
+            break;
+        }
+        let group_start = group_end - group.len();
+        let mut bytes = match bytes(group, fmt) {
+            IResult::Done("", bytes) => bytes,
+            _ => {
+                let (index, message) = diagnose_values(group, fmt);
+                return Err((column(input, group_start + index), message));
+            }
+        };
+        bytes.reverse();
+        data.extend(bytes);
+        if group.len() < width {
+            break;
+        }
+    }
+    Ok(Line { address, data })
+}
+
+/// Parses a single line of a dump whose address isn't terminated by a colon (hexdump, od).
+///
+/// The values end at `end`, which is the start of the interpretation column.
+/// A line which consists of an address only (the size of the dumped data) contains no data.
+fn offset_line(input: &str, end: char) -> Result<Line, (usize, String)> {
+    let digits = input
+        .find(|c: char| !c.is_ascii_hexdigit())
+        .unwrap_or(input.len());
+    let separated = digits == input.len() || input[digits..].starts_with(char::is_whitespace);
+    let address = match hex_string_to_u64(&input[..digits]) {
+        Ok(address) if separated => address,
+        _ => return Err((1, "expected hex address".to_string())),
+    };
+    let values = match input[digits..].find(end) {
+        Some(end) => &input[digits..digits + end],
+        None => &input[digits..],
+    };
+    match bytes(values, Format::Hex) {
+        IResult::Done(rest, data) if rest.trim().is_empty() => Ok(Line { address, data }),
+        _ => {
+            let (index, message) = diagnose_values(values, Format::Hex);
+            Err((column(input, digits + index), message))
+        }
+    }
+}
+
+/// Enum which provides all dump formats which can be read by the `LineReader`.
+#[derive(Debug, PartialEq, Clone, Copy)]
+pub enum Dialect {
+    /// Dumps of xxd-rs and GNU xxd, e.g. `00000000: 6865 6c6c  hell`
+    Xxd,
+    /// Dumps of `hexdump -C`, e.g. `00000000  68 65 6c 6c  |hell|`
+    Hexdump,
+    /// Dumps of `od -A x -t x1z`, e.g. `000000 68 65 6c 6c  >hell<`
+    Od,
+}
+
+impl From<String> for Dialect {
+    fn from(dialect: String) -> Self {
+        match dialect.as_ref() {
+            "xxd" => Dialect::Xxd,
+            "hexdump" => Dialect::Hexdump,
+            "od" => Dialect::Od,
+            _ => panic!("Invalid dump dialect"),
+        }
+    }
+}
+
+impl Dialect {
+    /// Recognises the dialect of a dump by one of its lines.
+    pub fn detect(line: &str) -> Dialect {
+        let line = line.trim_end();
+        if line.ends_with('|') && line.contains("  |") {
+            Dialect::Hexdump
+        } else if line.ends_with('<') && line.contains("  >") {
+            Dialect::Od
+        } else {
+            Dialect::Xxd
+        }
+    }
+}
+
+/// The `LineReader` reads a dump line by line.
+///
+/// Only a single line is kept in memory, so dumps of any size can be processed.
+/// Empty lines are skipped, lines without an address continue where the previous line ended.
+/// A `*` line repeats the previous line until the address of the next line is reached.
+/// A line which can't be parsed results in an error, reading can be continued afterwards.
+pub struct LineReader<R> {
+    reader: R,
+    fmt: Format,
+    dialect: Option<Dialect>,
+    plain: bool,
+    endianness: Endianness,
+    addressing: Addressing,
+    /// Group size and columns of a little-endian dump.
+    layout: (usize, usize),
+    buffer: String,
+    digits: String,
+    number: usize,
+    offset: u64,
+    previous: Vec<u8>,
+    squeezed: bool,
+    repeat_until: Option<u64>,
+    stashed: Option<Line>,
+}
+
+impl<R: BufRead> LineReader<R> {
+    pub fn new(reader: R, fmt: Format) -> LineReader<R> {
+        LineReader {
+            reader,
+            fmt,
+            dialect: None,
+            plain: false,
+            endianness: Endianness::Big,
+            addressing: Addressing::default(),
+            layout: (4, 4),
+            buffer: String::new(),
+            digits: String::new(),
+            number: 0,
+            offset: 0,
+            previous: Vec::new(),
+            squeezed: false,
+            repeat_until: None,
+            stashed: None,
+        }
+    }
+
+    /// Sets the dialect of the dump, by default it is detected using the first line.
+    pub fn dialect(mut self, dialect: Dialect) -> Self {
+        self.dialect = Some(dialect);
+        self
+    }
+
+    /// Reads a plain dump (postscript style) instead.
+    ///
+    /// Whitespace and line breaks are ignored, therefore the line width of the dump does not
+    /// matter and a value may even be split across two lines.
+    pub fn plain(mut self, plain: bool) -> Self {
+        self.plain = plain;
+        self
+    }
+
+    /// Reads a dump whose groups show their bytes in little-endian order (`dump -e`).
+    pub fn endianness(mut self, endianness: Endianness) -> Self {
+        self.endianness = endianness;
+        self
+    }
+
+    /// Group size of a little-endian dump (default: 4), see `Config::group_size`.
+    pub fn group_size(mut self, group_size: usize) -> Self {
+        self.layout.0 = group_size;
+        self
+    }
+
+    /// Amount of groups per line of a little-endian dump (default: 4), see `Config::columns`.
+    pub fn columns(mut self, columns: usize) -> Self {
+        self.layout.1 = columns;
+        self
+    }
+
+    /// Reads addresses written in `address_format` (see `Config::address_format`).
+    pub fn address_format(mut self, address_format: AddressFormat) -> Self {
+        self.addressing.radix = address_format.radix();
+        self
+    }
+
+    /// Subtracts `offset` from the addresses of a dump created with `Config::display_offset`.
+    ///
+    /// Lines which also show the file offset (`Config::show_file_offset`) are placed at the
+    /// file offset instead.
+    pub fn display_offset(mut self, offset: u64) -> Self {
+        self.addressing.display_offset = offset;
+        self
+    }
+
+    fn syntax_error(&self, column: usize, message: String) -> ParseError {
+        ParseError::Syntax {
+            line: self.number,
+            column,
+            message,
+        }
+    }
+
+    fn plain_line(&mut self) -> Result<Line, ParseError> {
+        let text = self.buffer.trim_end();
+        let mut digits = self.digits.clone();
+        let mut data = Vec::new();
+        for (index, c) in text.char_indices().filter(|(_, c)| !c.is_whitespace()) {
+            if !c.is_digit(self.fmt.radix()) {
+                let message = format!("expected {}, found '{}'", digit_name(self.fmt), c);
+                return Err(self.syntax_error(column(text, index), message));
+            }
+            digits.push(c);
+            if digits.len() == self.fmt.width() {
+                match string_to_u8(&digits, self.fmt) {
+                    Ok(byte) => data.push(byte),
+                    Err(_) => {
+                        let message = format!("value '{}' does not fit into a byte", digits);
+                        return Err(self.syntax_error(column(text, index), message));
+                    }
+                }
+                digits.clear();
+            }
+        }
+        self.digits = digits;
+        Ok(Line {
+            address: self.offset,
+            data,
+        })
+    }
+
+    fn parse_line(&mut self) -> Result<Line, ParseError> {
+        if self.plain {
+            return self.plain_line();
+        }
+        let text = self.buffer.trim_end();
+        let dialect = *self.dialect.get_or_insert_with(|| Dialect::detect(text));
+        let line = match dialect {
+            Dialect::Xxd if self.endianness == Endianness::Little => {
+                little_endian_line(text, self.offset, self.fmt, self.addressing, self.layout)
+            }
+            Dialect::Xxd => hexdum_line(text, self.offset, self.fmt, self.addressing),
+            Dialect::Hexdump => offset_line(text, '|'),
+            Dialect::Od => offset_line(text, '>'),
+        };
+        line.map_err(|(column, message)| self.syntax_error(column, message))
+    }
+
+    /// Returns the next copy of the previous line while a `*` line is expanded.
+    fn repeat_previous(&mut self) -> Option<Line> {
+        let end = self.repeat_until?;
+        if self.previous.is_empty() || self.offset >= end {
+            self.repeat_until = None;
+            return None;
+        }
+        let length = std::cmp::min(self.previous.len() as u64, end - self.offset) as usize;
+        let line = Line {
+            address: self.offset,
+            data: self.previous[..length].to_vec(),
+        };
+        self.offset += length as u64;
+        Some(line)
+    }
+
+    /// Remembers `line` as the previous line, lines without data (end addresses) are dropped.
+    fn accept(&mut self, line: Line) -> Option<Line> {
+        self.offset = line.address + line.data.len() as u64;
+        if line.data.is_empty() {
+            return None;
+        }
+        self.previous = line.data.clone();
+        Some(line)
+    }
+}
+
+impl<R: BufRead> Iterator for LineReader<R> {
+    type Item = Result<Line, ParseError>;
+
+    fn next(&mut self) -> Option<Self::Item> {
+        loop {
+            if let Some(line) = self.repeat_previous() {
+                return Some(Ok(line));
+            }
+            if let Some(line) = self.stashed.take() {
+                match self.accept(line) {
+                    Some(line) => return Some(Ok(line)),
+                    None => continue,
+                }
+            }
+            self.buffer.clear();
+            match self.reader.read_line(&mut self.buffer) {
+                Ok(0) if self.plain && !self.digits.is_empty() => {
+                    let message =
+                        format!("incomplete value '{}' at the end of the input", self.digits);
+                    self.digits.clear();
+                    return Some(Err(self.syntax_error(1, message)));
+                }
+                Ok(0) => return None,
+                Ok(_) => self.number += 1,
+                Err(error) => return Some(Err(error.into())),
+            }
+            // rulers (see `Config::ruler`) don't contain data
+            if self.buffer.trim().is_empty() || self.buffer.starts_with(RULER_MARKER) {
+                continue;
+            }
+            if !self.plain && self.buffer.trim() == "*" {
+                self.squeezed = true;
+                continue;
+            }
+            match self.parse_line() {
+                Ok(line) if self.squeezed => {
+                    self.squeezed = false;
+                    self.repeat_until = Some(line.address);
+                    self.stashed = Some(line);
+                }
+                Ok(line) => {
+                    if let Some(line) = self.accept(line) {
+                        return Some(Ok(line));
+                    }
+                }
+                Err(error) => return Some(Err(error)),
+            }
+        }
+    }
+}
+
+/// Reverts the lines of a dump back into the original binary data.
+///
+/// The addresses of the lines are honored, gaps between lines are filled with zeros.
+/// Because `writer` can't be rewound, addresses must be in ascending order.
+pub fn revert<I>(lines: I, writer: &mut dyn Write) -> Result<(), anyhow::Error>
+where
+    I: IntoIterator<Item = Result<Line, ParseError>>,
+{
+    let mut position: u64 = 0;
+    for line in lines {
+        let line = line?;
+        if line.address < position {
+            return Err(anyhow::anyhow!(
+                "Address {:08X} lies before the current output position {:08X}",
+                line.address,
+                position
+            ));
+        }
+        io::copy(&mut io::repeat(0).take(line.address - position), writer)?;
+        writer.write_all(&line.data)?;
+        position = line.address + line.data.len() as u64;
+    }
+    Ok(())
+}
+
+/// Patches `writer` with the lines of a dump.
+///
+/// Every line is written at its address, the existing content in between and after
+/// the patched ranges is left untouched.
+pub fn patch<I, W>(lines: I, writer: &mut W) -> Result<(), anyhow::Error>
+where
+    I: IntoIterator<Item = Result<Line, ParseError>>,
+    W: Write + Seek,
+{
+    for line in lines {
+        let line = line?;
+        writer.seek(SeekFrom::Start(line.address))?;
+        writer.write_all(&line.data)?;
+    }
+    Ok(())
+}
+
+/// Parses the address of a dump line found in arbitrary text, e.g. `0x0010:` or `00000010`.
+///
+/// Returns the address and whether the token is certainly an address (`0x` prefix or `:` suffix).
+fn text_address(token: &str) -> Option<(u64, bool)> {
+    let digits = token.trim_end_matches(':');
+    let (digits, certain) = match digits.get(..2) {
+        Some("0x") | Some("0X") => (&digits[2..], true),
+        _ => (digits, digits.len() != token.len()),
+    };
+    match hex_string_to_u64(digits) {
+        Ok(address) if digits.chars().all(|c| c.is_ascii_hexdigit()) => Some((address, certain)),
+        _ => None,
+    }
+}
+
+fn is_value_token(token: &str) -> bool {
+    token.len() >= 2
+        && token.len() <= 16
+        && token.len().is_multiple_of(2)
+        && token.chars().all(|c| c.is_ascii_hexdigit())
+}
+
+/// Checks whether `text` is the interpretation column of `data`, e.g. `|hello..|` or `hello..`.
+fn is_interpretation(text: &str, data: &[u8]) -> bool {
+    let text = text
+        .trim()
+        .trim_start_matches(['|', '>'])
+        .trim_end_matches(['|', '<']);
+    text.chars().count() == data.len()
+        && text
+            .chars()
+            .zip(data.iter())
+            .all(|(c, b)| c == '.' || c == *b as char)
+}
+
+/// Finds a dump line within a line of arbitrary text, e.g. a line of a kernel log.
+///
+/// A dump line consists of an optional address and hex values of equal width,
+/// without an address at least 8 bytes are needed to recognise it as such.
+fn find_dump(text: &str) -> Option<(Option<u64>, Vec<u8>)> {
+    let tokens: Vec<(usize, &str)> = text
+        .split_whitespace()
+        .map(|token| (token.as_ptr() as usize - text.as_ptr() as usize, token))
+        .collect();
+    for start in 0..tokens.len() {
+        let (mut address, mut first) = (None, start);
+        if let Some((value, certain)) = text_address(tokens[start].1) {
+            let next_width = tokens.get(start + 1).map(|(_, token)| token.len());
+            if certain || (next_width.is_some() && next_width != Some(tokens[start].1.len())) {
+                address = Some(value);
+                first = start + 1;
+            }
+        }
+        let width = match tokens.get(first) {
+            Some((_, token)) if is_value_token(token) => token.len(),
+            _ => continue,
+        };
+        let values: Vec<(usize, &str)> = tokens[first..]
+            .iter()
+            .take_while(|(_, token)| token.len() == width && is_value_token(token))
+            .cloned()
+            .collect();
+        let mut data: Vec<u8> = Vec::new();
+        for (index, (_, token)) in values.iter().enumerate() {
+            if is_interpretation(&text[values[index].0..], &data) {
+                break;
+            }
+            data.extend(
+                (0..token.len())
+                    .step_by(2)
+                    .map(|i| u8::from_str_radix(&token[i..i + 2], 16).unwrap_or(0)),
+            );
+        }
+        if address.is_some() || data.len() >= 8 {
+            return Some((address, data));
+        }
+    }
+    None
+}
+
+/// A dump found within arbitrary text, e.g. a log file.
+#[derive(Debug, PartialEq)]
+pub struct Blob {
+    /// First line (1 based) of the dump within the text
+    pub first_line: usize,
+    /// Last line (1 based) of the dump within the text
+    pub last_line: usize,
+    /// Address of the first byte
+    pub address: u64,
+    pub data: Vec<u8>,
+}
+
+impl Blob {
+    fn end(&self) -> u64 {
+        self.address + self.data.len() as u64
+    }
+}
+
+/// The `Scanner` finds dumps within arbitrary text like kernel logs, `tcpdump -X` output
+/// or hex dumps copied from Wireshark.
+///
+/// Prefixes (e.g. timestamps) of the lines are stripped. Consecutive dump lines are merged
+/// into a single `Blob` as long as their addresses are contiguous. A `*` line repeats the
+/// previous line until the address of the next line is reached.
+pub struct Scanner<R> {
+    reader: R,
+    buffer: String,
+    number: usize,
+    blob: Option<Blob>,
+    previous: Vec<u8>,
+    squeezed: bool,
+}
+
+impl<R: BufRead> Scanner<R> {
+    pub fn new(reader: R) -> Scanner<R> {
+        Scanner {
+            reader,
+            buffer: String::new(),
+            number: 0,
+            blob: None,
+            previous: Vec::new(),
+            squeezed: false,
+        }
+    }
+
+    /// Adds a dump line to the current blob, returns the previous blob if a new one is started.
+    fn add(&mut self, address: Option<u64>, data: Vec<u8>) -> Option<Blob> {
+        let mut finished = None;
+        if let (Some(blob), Some(address)) = (&mut self.blob, address) {
+            if self.squeezed && address > blob.end() && !self.previous.is_empty() {
+                let repeated = (address - blob.end()) as usize;
+                let previous = self.previous.iter().cycle().take(repeated);
+                blob.data.extend(previous);
+            }
+            if address != blob.end() {
+                finished = self.blob.take();
+            }
+        }
+        self.squeezed = false;
+        let number = self.number;
+        let blob = self.blob.get_or_insert_with(|| Blob {
+            first_line: number,
+            last_line: number,
+            address: address.unwrap_or(0),
+            data: Vec::new(),
+        });
+        blob.last_line = number;
+        blob.data.extend(&data);
+        self.previous = data;
+        finished
+    }
+}
+
+impl<R: BufRead> Iterator for Scanner<R> {
+    type Item = Result<Blob, io::Error>;
+
+    fn next(&mut self) -> Option<Self::Item> {
+        loop {
+            self.buffer.clear();
+            match self.reader.read_line(&mut self.buffer) {
+                Ok(0) => return self.blob.take().map(Ok),
+                Ok(_) => self.number += 1,
+                Err(error) => return Some(Err(error)),
+            }
+            if let Some((address, data)) = find_dump(&self.buffer) {
+                if let Some(blob) = self.add(address, data) {
+                    return Some(Ok(blob));
+                }
+            } else if self.blob.is_some() && self.buffer.split_whitespace().last() == Some("*") {
+                self.squeezed = true;
+            } else if let Some(blob) = self.blob.take() {
+                return Some(Ok(blob));
+            }
+        }
+    }
+}
+
+/// Returns the offset and the content of the first array within a generated source file.
+///
+/// The array starts at the first bracket after the assignment and ends at the first closing
+/// bracket, this way numbers within the declaration (e.g. `u8`) or after the array
+/// (e.g. a length variable) are not mistaken for data.
+fn array_body(input: &str) -> (usize, &str) {
+    let is_open = |c: char| c == '{' || c == '[';
+    let is_close = |c: char| c == '}' || c == ']';
+    let start = input.find('=').map_or(0, |p| p + 1);
+    match input[start..].find(is_open) {
+        Some(open) => {
+            let start = start + open + 1;
+            let end = input[start..]
+                .find(is_close)
+                .map_or(input.len(), |close| start + close);
+            (start, &input[start..end])
+        }
+        None => (start, &input[start..]),
+    }
+}
+
+/// Splits source code into its words (identifiers and literals), comments are skipped.
+fn words(source: &str) -> Vec<(usize, &str)> {
+    let mut words = Vec::new();
+    let mut chars = source.char_indices().peekable();
+    let mut word_start = None;
+    while let Some((index, c)) = chars.next() {
+        if c.is_ascii_alphanumeric() || c == '_' {
+            word_start.get_or_insert(index);
+            continue;
+        }
+        if let Some(start) = word_start.take() {
+            words.push((start, &source[start..index]));
+        }
+        let next = chars.peek().map(|(_, c)| *c);
+        if c == '#' || (c == '/' && next == Some('/')) {
+            while chars.next_if(|(_, c)| *c != '\n').is_some() {}
+        } else if c == '/' && next == Some('*') {
+            chars.next();
+            let mut previous = ' ';
+            for (_, c) in chars.by_ref() {
+                if previous == '*' && c == '/' {
+                    break;
+                }
+                previous = c;
+            }
+        }
+    }
+    if let Some(start) = word_start {
+        words.push((start, &source[start..]));
+    }
+    words
+}
+
+/// Converts a hex, octal, binary or decimal integer literal (C, C++, Rust or Python) into a byte.
+fn literal_to_u8(literal: &str) -> Result<u8, ParseIntError> {
+    let value = match literal.find(|c| "uUiIlL".contains(c)) {
+        Some(suffix) => &literal[..suffix],
+        None => literal,
+    };
+    let value = value.replace('_', "");
+    let (digits, radix) = match value.get(..2) {
+        Some("0x") | Some("0X") => (&value[2..], 16),
+        Some("0o") | Some("0O") => (&value[2..], 8),
+        Some("0b") | Some("0B") => (&value[2..], 2),
+        _ if value.len() > 1 && value.starts_with('0') => (&value[1..], 8),
+        _ => (&value[..], 10),
+    };
+    u8::from_str_radix(digits, radix)
+}
+
+/// Reverts a source file created by the generate module back into the original binary data.
+///
+/// The integer literals of the first array are converted into bytes, therefore custom
+/// prefixes, suffixes and separators are supported as long as the separator does not
+/// contain letters or digits.
+pub fn revert_source(input: &str, writer: &mut dyn Write) -> Result<(), anyhow::Error> {
+    let (offset, body) = array_body(input);
+    let mut data = Vec::new();
+    for (index, word) in words(body) {
+        match literal_to_u8(word) {
+            Ok(byte) => data.push(byte),
+            Err(_) => {
+                let index = offset + index;
+                let line_start = input[..index].rfind('\n').map_or(0, |p| p + 1);
+                return Err(ParseError::Syntax {
+                    line: input[..index].matches('\n').count() + 1,
+                    column: column(&input[line_start..], index - line_start),
+                    message: format!("invalid byte literal '{}'", word),
+                }
+                .into());
+            }
+        }
+    }
+    writer.write_all(&data)?;
+    Ok(())
+}
+
+/// Converts loosely formatted hex text into bytes.
+///
+/// Values may be separated by whitespace, commas or colons and may be prefixed with `0x`
+/// or `\x`, e.g. `DE AD be ef`, `0xDE,0xAD`, `\xde\xad` or `de:ad:be:ef`.
+pub fn hex_text(input: &str) -> Result<Vec<u8>, ParseError> {
+    let mut data = Vec::new();
+    for (number, text) in input.lines().enumerate() {
+        let error = |index: usize, message: String| ParseError::Syntax {
+            line: number + 1,
+            column: column(text, index),
+            message,
+        };
+        // replacing the escapes by the same amount of spaces keeps the offsets intact
+        let line = text.replace("\\x", "  ");
+        let tokens = line
+            .split(|c: char| c.is_whitespace() || c == ',' || c == ':')
+            .filter(|token| !token.is_empty());
+        for token in tokens {
+            let start = token.as_ptr() as usize - line.as_ptr() as usize;
+            let (start, digits) = match token.get(..2) {
+                Some("0x") | Some("0X") => (start + 2, &token[2..]),
+                _ => (start, token),
+            };
+            if let Some(index) = digits.find(|c: char| !c.is_ascii_hexdigit()) {
+                let c = digits[index..].chars().next().unwrap_or(' ');
+                let message = format!("expected hex digit, found '{}'", c);
+                return Err(error(start + index, message));
+            }
+            let digits = match digits.len() {
+                0 => return Err(error(start, "expected hex digit".to_string())),
+                1 if token.len() != digits.len() => format!("0{}", digits),
+                n if n % 2 == 1 => {
+                    let message = format!("odd number of hex digits in '{}'", digits);
+                    return Err(error(start, message));
+                }
+                _ => digits.to_string(),
+            };
+            for i in (0..digits.len()).step_by(2) {
+                data.push(hex_string_to_u64(&digits[i..i + 2]).unwrap_or(0) as u8);
+            }
+        }
+    }
+    Ok(data)
+}
 
 #[cfg(test)]
 mod test {
 
     use super::*;
-    use nom::IResult;
+    use crate::dump::{dump_iterator, AddressFormat, Config, Format};
+    use crate::generate::{Language, Render, Template};
+    use std::io::Cursor;
 
     #[test]
     fn address_parser() {
         {
-            let result = address("00112233: some additional garbage");
-            assert_eq!(IResult::Done(" some additional garbage", 112233), result);
+            let result = address("00112233: some additional garbage", 16);
+            assert_eq!(
+                IResult::Done(" some additional garbage", (None, 0x112233)),
+                result
+            );
+        }
+        {
+            let result = address("0000000A: 00 FF", 16);
+            assert_eq!(IResult::Done(" 00 FF", (None, 0xA)), result);
         }
     }
 
+    #[test]
+    fn number_parser() {
+        assert_eq!(1024, parse_number("1024").unwrap());
+        assert_eq!(0x0800_0000, parse_number(" 0x08000000").unwrap());
+        assert_eq!(0xff, parse_number("0XfF").unwrap());
+        let error = parse_number("0x").unwrap_err();
+        assert_eq!("invalid number '0x'", error.to_string());
+        assert!(parse_number("-1").is_err());
+    }
+
     #[test]
     fn bytes_parser() {
         {
-            let result = bytes("AABBCCEE");
+            let result = bytes("AABBCCEE", Format::Hex);
             assert_eq!(IResult::Done("", vec![0xAA, 0xBB, 0xCC, 0xEE]), result);
         }
         {
-            let result = bytes(" AA BB CC EE");
+            let result = bytes(" AA BB CC EE", Format::Hex);
             assert_eq!(IResult::Done("", vec![0xAA, 0xBB, 0xCC, 0xEE]), result);
         }
         {
-            let result = bytes(" AA BB CC EE\n BB DD");
+            let result = bytes(" AA BB CC EE\n BB DD", Format::Hex);
             assert_eq!(
                 IResult::Done("", vec![0xAA, 0xBB, 0xCC, 0xEE, 0xBB, 0xDD]),
                 result
             );
         }
         {
-            let result = bytes(" AA BB CC EE xxx  BB DD");
+            let result = bytes(" AA BB CC EE xxx  BB DD", Format::Hex);
             assert_eq!(
                 IResult::Done("xxx  BB DD", vec![0xAA, 0xBB, 0xCC, 0xEE]),
                 result
             )
         }
+        {
+            let result = bytes("000 377 120054", Format::Octal);
+            assert_eq!(IResult::Done("", vec![0, 255, 80, 44]), result);
+        }
+        {
+            let result = bytes("000 255 080044", Format::Decimal);
+            assert_eq!(IResult::Done("", vec![0, 255, 80, 44]), result);
+        }
+        {
+            let result = bytes("00000000 1111111101010000", Format::Binary);
+            assert_eq!(IResult::Done("", vec![0, 255, 80]), result);
+        }
+        {
+            let result = bytes("255 256", Format::Decimal);
+            assert_eq!(IResult::Done("256", vec![255]), result);
+        }
     }
 
     #[test]
     fn hexdump_line_parser() {
         {
-            let expected_result = IResult::Done(
-                "",
+            let expected_result = Line {
+                address: 0x112233,
+                data: vec![0xAA, 0xBB, 0xCC, 0xEE],
+            };
+            let result = hexdum_line(
+                "00112233: AA BB CC EE   ....",
+                0,
+                Format::Hex,
+                Addressing::default(),
+            )
+            .unwrap();
+            assert_eq!(expected_result, result);
+        }
+        {
+            let expected_result = Line {
+                address: 0xA0,
+                data: vec![0x20, 0x3C, 0x6E, 0x69, 0x63],
+            };
+            let result = hexdum_line(
+                "000000A0: 203C 6E69 63              <nic",
+                0,
+                Format::Hex,
+                Addressing::default(),
+            )
+            .unwrap();
+            assert_eq!(expected_result, result);
+        }
+        {
+            let expected_result = Line {
+                address: 16,
+                data: vec![0x5b, 0x70, 0x61, 0x63],
+            };
+            let result = hexdum_line("5b706163", 16, Format::Hex, Addressing::default()).unwrap();
+            assert_eq!(expected_result, result);
+        }
+        {
+            let result = hexdum_line(
+                "00000000: AA BB C   ...",
+                0,
+                Format::Hex,
+                Addressing::default(),
+            );
+            assert_eq!(
+                Err((17, "expected 2 digits per value, found 'C'".to_string())),
+                result
+            );
+        }
+        {
+            let result = hexdum_line(
+                "00000010: 0102 0G04  ....",
+                0,
+                Format::Hex,
+                Addressing::default(),
+            );
+            assert_eq!(
+                Err((17, "expected hex digit, found 'G'".to_string())),
+                result
+            );
+        }
+        {
+            let result = hexdum_line(
+                "00000010: 255 256  ..",
+                0,
+                Format::Decimal,
+                Addressing::default(),
+            );
+            assert_eq!(
+                Err((15, "value '256' does not fit into a byte".to_string())),
+                result
+            );
+        }
+    }
+
+    #[test]
+    fn revert_dump() {
+        let data: Vec<u8> = (0..=255).collect();
+        let settings = [
+            Config::new(),
+            Config::new().format(Format::Hex).group_size(2),
+            Config::new().group_size(4).columns(3),
+            Config::new().show_interpretation(false),
+            Config::new()
+                .separator(false)
+                .show_address(false)
+                .show_interpretation(false),
+        ];
+        let formats = [
+            Format::HexUpperCase,
+            Format::Hex,
+            Format::Octal,
+            Format::Decimal,
+            Format::Binary,
+        ];
+        let address_formats = [
+            AddressFormat::HexUpperCase,
+            AddressFormat::Hex,
+            AddressFormat::Decimal,
+            AddressFormat::Octal,
+        ];
+        for config in settings.iter() {
+            for fmt in formats.iter() {
+                for address_format in address_formats.iter() {
+                    for display_offset in [0, 0x0800_0000].iter() {
+                        let config = config
+                            .format(*fmt)
+                            .address_format(*address_format)
+                            .display_offset(*display_offset);
+                        let mut dump: Vec<u8> = Vec::new();
+                        dump_iterator(data.iter().cloned(), &mut dump, config).unwrap();
+                        let lines = LineReader::new(dump.as_slice(), *fmt)
+                            .address_format(*address_format)
+                            .display_offset(*display_offset);
+                        let mut result: Vec<u8> = Vec::new();
+                        revert(lines, &mut result).unwrap();
+                        assert_eq!(data, result);
+                    }
+                }
+            }
+        }
+    }
+
+    fn revert_plain(input: &str, fmt: Format) -> Result<Vec<u8>, anyhow::Error> {
+        let mut result: Vec<u8> = Vec::new();
+        revert(
+            LineReader::new(input.as_bytes(), fmt).plain(true),
+            &mut result,
+        )?;
+        Ok(result)
+    }
+
+    #[test]
+    fn revert_plain_dump() {
+        let data: Vec<u8> = (0..=255).collect();
+        for columns in [1, 7, 16, 30].iter() {
+            let config = Config::new()
+                .columns(*columns)
+                .separator(false)
+                .show_address(false)
+                .show_interpretation(false);
+            let mut dump: Vec<u8> = Vec::new();
+            dump_iterator(data.iter().cloned(), &mut dump, config).unwrap();
+            let result = revert_plain(&String::from_utf8(dump).unwrap(), Format::Hex).unwrap();
+            assert_eq!(data, result);
+        }
+        {
+            let result = revert_plain("0000000011\n111111\n", Format::Binary).unwrap();
+            assert_eq!(vec![0x00, 0xFF], result);
+        }
+        {
+            let result = revert_plain("5b7\n061 636b\r\n  6167\n", Format::Hex).unwrap();
+            assert_eq!(vec![0x5b, 0x70, 0x61, 0x63, 0x6b, 0x61, 0x67], result);
+        }
+        {
+            let error = revert_plain("5b70\n61xx\n", Format::Hex).unwrap_err();
+            assert_eq!(
+                "line 2, column 3: expected hex digit, found 'x'",
+                error.to_string()
+            );
+            let error = revert_plain("5b706", Format::Hex).unwrap_err();
+            assert_eq!(
+                "line 1, column 1: incomplete value '6' at the end of the input",
+                error.to_string()
+            );
+            assert!(revert_plain("01201", Format::Binary).is_err());
+        }
+    }
+
+    #[test]
+    fn revert_fills_gaps_with_zeros() {
+        let dump = "00000002: 0102  ..\n00000006: 0304  ..\n";
+        let mut result: Vec<u8> = Vec::new();
+        revert(LineReader::new(dump.as_bytes(), Format::Hex), &mut result).unwrap();
+        assert_eq!(vec![0, 0, 1, 2, 0, 0, 3, 4], result);
+    }
+
+    #[test]
+    fn revert_rejects_descending_addresses() {
+        let dump = "00000006: 0304  ..\n00000002: 0102  ..\n";
+        let mut result: Vec<u8> = Vec::new();
+        assert!(revert(LineReader::new(dump.as_bytes(), Format::Hex), &mut result).is_err());
+    }
+
+    #[test]
+    fn patch_only_overwrites_dumped_ranges() {
+        let dump = "00000006: 0304  ..\n00000002: 0102  ..\n";
+        let mut file = Cursor::new(vec![0xFF; 10]);
+        patch(LineReader::new(dump.as_bytes(), Format::Hex), &mut file).unwrap();
+        assert_eq!(
+            vec![0xFF, 0xFF, 1, 2, 0xFF, 0xFF, 3, 4, 0xFF, 0xFF],
+            file.into_inner()
+        );
+    }
+
+    #[test]
+    fn literal_parser() {
+        assert_eq!(Ok(0x1A), literal_to_u8("0x1A"));
+        assert_eq!(Ok(0x1A), literal_to_u8("0X1a"));
+        assert_eq!(Ok(0x1A), literal_to_u8("0x1Au8"));
+        assert_eq!(Ok(26), literal_to_u8("26"));
+        assert_eq!(Ok(26), literal_to_u8("26U"));
+        assert_eq!(Ok(0), literal_to_u8("0"));
+        assert_eq!(Ok(0o17), literal_to_u8("017"));
+        assert_eq!(Ok(0o17), literal_to_u8("0o17"));
+        assert_eq!(Ok(0b101), literal_to_u8("0b0000_0101"));
+        assert!(literal_to_u8("256").is_err());
+        assert!(literal_to_u8("data").is_err());
+        assert!(literal_to_u8("089").is_err());
+    }
+
+    #[test]
+    fn revert_generated_sources() {
+        let data: Vec<u8> = (0..=255).collect();
+        let languages = [
+            Language::C,
+            Language::Cpp,
+            Language::Rust,
+            Language::Python,
+            Language::Unknown,
+        ];
+        for lang in languages.iter() {
+            for separator in [",", ";", " |", ""].iter() {
+                let mut template = Template::new(*lang);
+                template.set_separator(separator.to_string());
+                let source = template.render(&data);
+                let mut result: Vec<u8> = Vec::new();
+                revert_source(&source, &mut result).unwrap();
+                assert_eq!(data, result);
+            }
+        }
+    }
+
+    #[test]
+    fn revert_handwritten_sources() {
+        let source = r#"
+/* vendor blob, do not edit */
+unsigned char firmware_bin[4] = {
+    0xde, 0XAD, // magic
+    255, 017,
+};
+unsigned int firmware_bin_len = 4;
+"#;
+        let mut result: Vec<u8> = Vec::new();
+        revert_source(source, &mut result).unwrap();
+        assert_eq!(vec![0xDE, 0xAD, 0xFF, 0o17], result);
+
+        let source = "data = [\n    0x01, 0x02,\n    0x03, 0x100\n]";
+        let error = revert_source(source, &mut result).unwrap_err();
+        assert_eq!(
+            "line 3, column 11: invalid byte literal '0x100'",
+            error.to_string()
+        );
+    }
+
+    #[test]
+    fn line_reader() {
+        let dump = "00000010: 0102  ..\n\n0304  ..\n00000020: 05\n";
+        let lines: Vec<Line> = LineReader::new(dump.as_bytes(), Format::Hex)
+            .map(|line| line.unwrap())
+            .collect();
+        assert_eq!(
+            vec![
                 Line {
-                    address: 112233,
-                    data: vec![0xAA, 0xBB, 0xCC, 0xEE],
+                    address: 0x10,
+                    data: vec![1, 2]
                 },
-            );
-            let result = hexdum_line("00112233: AA BB CC EE   ....\n");
-            assert_eq!(expected_result, result);
+                Line {
+                    address: 0x12,
+                    data: vec![3, 4]
+                },
+                Line {
+                    address: 0x20,
+                    data: vec![5]
+                },
+            ],
+            lines
+        );
+    }
+
+    #[test]
+    fn line_reader_reports_invalid_lines() {
+        let dump = "00000000: 0102  ..\n\n00000002: 03XX  ..\n00000004: 0506  ..\n";
+        let mut reader = LineReader::new(dump.as_bytes(), Format::Hex);
+        assert!(reader.next().unwrap().is_ok());
+        match reader.next() {
+            Some(Err(ParseError::Syntax { line, column, .. })) => {
+                assert_eq!(3, line);
+                assert_eq!(13, column);
+            }
+            other => panic!("unexpected result: {:?}", other),
         }
+        assert_eq!(
+            Line {
+                address: 4,
+                data: vec![5, 6]
+            },
+            reader.next().unwrap().unwrap()
+        );
+        assert!(reader.next().is_none());
+    }
+
+    #[test]
+    fn detect_dialect() {
+        assert_eq!(
+            Dialect::Xxd,
+            Dialect::detect("00000000: 6865 6c6c 6f20 776f 726c 640a       hello world.")
+        );
+        assert_eq!(
+            Dialect::Hexdump,
+            Dialect::detect(
+                "00000000  68 65 6c 6c 6f 20 77 6f  72 6c 64 0a              |hello world.|"
+            )
+        );
+        assert_eq!(
+            Dialect::Od,
+            Dialect::detect(
+                "000000 68 65 6c 6c 6f 20 77 6f 72 6c 64 0a              >hello world.<\n"
+            )
+        );
+    }
+
+    fn read_all(dump: &str) -> Vec<u8> {
+        let mut result: Vec<u8> = Vec::new();
+        revert(LineReader::new(dump.as_bytes(), Format::Hex), &mut result).unwrap();
+        result
+    }
+
+    #[test]
+    fn revert_gnu_xxd_dump() {
+        let dump = r#"00000000: 6865 6c6c 6f20 776f 726c 640a 0000 0000  hello world.....
+00000010: 0000 0000 0000 0000 0000 0000 0000 00ff  ................
+00000020: 21                                       !
+"#;
+        let mut expected = b"hello world\n".to_vec();
+        expected.extend(vec![0; 19]);
+        expected.extend(vec![0xff, 0x21]);
+        assert_eq!(expected, read_all(dump));
+        let dump = "00000000: 68 65 6c 6c 6f  hello\n00000005: 20 77        w\n";
+        assert_eq!(b"hello w".to_vec(), read_all(dump));
+    }
+
+    #[test]
+    fn revert_hexdump_dump() {
+        let dump = r#"00000000  68 65 6c 6c 6f 20 77 6f  72 6c 64 0a ff ff ff ff  |hello world.....|
+00000010  ff ff ff ff ff ff ff ff  ff ff ff ff ff ff ff ff  |................|
+*
+00000040  21                                                |!|
+00000041
+"#;
+        let mut expected = b"hello world\n".to_vec();
+        expected.extend(vec![0xff; 52]);
+        expected.push(0x21);
+        assert_eq!(expected, read_all(dump));
+    }
+
+    #[test]
+    fn revert_od_dump() {
+        let dump = r#"000000 00 00 00 00 00 00 00 00 00 00 00 00 00 00 00 00  >................<
+*
+000060 00 00 00 01                                      >....<
+000064
+"#;
+        let mut expected = vec![0; 99];
+        expected.push(1);
+        assert_eq!(expected, read_all(dump));
+        let dump = "000000 68 65 6c 6c 6f\n000005\n";
+        let lines = LineReader::new(dump.as_bytes(), Format::Hex).dialect(Dialect::Od);
+        let mut result: Vec<u8> = Vec::new();
+        revert(lines, &mut result).unwrap();
+        assert_eq!(b"hello".to_vec(), result);
+    }
+
+    #[test]
+    fn squeezed_dump_ending_with_its_size() {
+        let dump = "00000000  ff ff  |..|\n*\n00000005\n";
+        assert_eq!(vec![0xff; 5], read_all(dump));
+    }
+
+    #[test]
+    fn revert_autoskipped_dump() {
+        let config = Config::new().autoskip(true);
+        for tail in [&b""[..], &b"end"[..]].iter() {
+            let mut data = vec![0xffu8; 100];
+            data.extend_from_slice(tail);
+            let mut dump: Vec<u8> = Vec::new();
+            dump_iterator(data.iter().cloned(), &mut dump, config).unwrap();
+            assert_eq!(data, read_all(std::str::from_utf8(&dump).unwrap()));
+        }
+    }
+
+    #[test]
+    fn revert_little_endian_dump() {
+        let layouts = [(4, 4, true), (2, 3, false), (8, 2, true)];
+        let text = b"abcdefg hijklmno, the quick brown fox".to_vec();
+        for (group_size, columns, interpretation) in layouts.iter() {
+            let config = Config::new()
+                .group_size(*group_size)
+                .columns(*columns)
+                .show_interpretation(*interpretation)
+                .endianness(Endianness::Little);
+            let mut inputs: Vec<Vec<u8>> = [3, 16, 23, 30, 33]
+                .iter()
+                .map(|length| (0..*length).collect())
+                .collect();
+            inputs.extend((1..=text.len()).map(|length| text[..length].to_vec()));
+            for data in inputs.iter() {
+                let mut dump: Vec<u8> = Vec::new();
+                dump_iterator(data.iter().cloned(), &mut dump, config).unwrap();
+                let mut result: Vec<u8> = Vec::new();
+                let lines = LineReader::new(dump.as_slice(), Format::Hex)
+                    .endianness(Endianness::Little)
+                    .group_size(*group_size)
+                    .columns(*columns);
+                revert(lines, &mut result).unwrap();
+                assert_eq!(data, &result);
+            }
+        }
+    }
+
+    #[test]
+    fn revert_dump_with_ruler() {
+        let config = Config::new().ruler(Some(2));
+        for length in [5, 100].iter() {
+            let data: Vec<u8> = (0..*length).collect();
+            let mut dump: Vec<u8> = Vec::new();
+            dump_iterator(data.iter().cloned(), &mut dump, config).unwrap();
+            assert!(dump.starts_with(b"#         "));
+            assert_eq!(data, read_all(std::str::from_utf8(&dump).unwrap()));
+        }
+    }
+
+    #[test]
+    fn revert_dump_with_display_offset() {
+        let data = b"hello world, hello xxd-rs".to_vec();
+        let config = Config::new()
+            .format(Format::Hex)
+            .display_offset(0x0800_0000)
+            .show_file_offset(true);
+        let mut dump: Vec<u8> = Vec::new();
+        dump_iterator(data.iter().cloned(), &mut dump, config).unwrap();
+        assert!(dump.starts_with(b"00000000 08000000: 68 65"));
+        assert_eq!(data, read_all(std::str::from_utf8(&dump).unwrap()));
+
+        let dump = "08000000: 68 65\n";
+        let lines = LineReader::new(dump.as_bytes(), Format::Hex).display_offset(0x0800_0010);
+        let error = revert(lines, &mut Vec::new()).unwrap_err();
+        assert_eq!(
+            "line 1, column 1: address lies before the display offset 8000010",
+            error.to_string()
+        );
+    }
+
+    #[test]
+    fn find_dumps_in_text() {
+        let hello = b"hello world\n".to_vec();
+        {
+            let line =
+                "[   12.345678] dump: 00000000: 68 65 6c 6c 6f 20 77 6f 72 6c 64 0a  hello world.";
+            assert_eq!(Some((Some(0), hello.clone())), find_dump(line));
+        }
+        {
+            let line = "0000   68 65 6c 6c 6f 20 77 6f 72 6c 64 0a   hello world.";
+            assert_eq!(Some((Some(0), hello.clone())), find_dump(line));
+        }
+        {
+            let line = "\t0x0010:  6865 6c6c 6f20 776f 726c 640a       hello.world.";
+            assert_eq!(Some((Some(0x10), hello.clone())), find_dump(line));
+        }
+        {
+            let line = "00000010  68 65 6c 6c 6f 20 77 6f  72 6c 64 0a  |hello world.|";
+            assert_eq!(Some((Some(0x10), hello.clone())), find_dump(line));
+        }
+        {
+            let line = "0010  61 62 ab   abab";
+            assert_eq!(Some((Some(0x10), vec![0x61, 0x62, 0xab])), find_dump(line));
+        }
+        {
+            let line = "0010  61 62   ab";
+            assert_eq!(Some((Some(0x10), vec![0x61, 0x62])), find_dump(line));
+        }
+        {
+            let line = "I (1234) tag: 68 65 6c 6c 6f 20 77 6f 72 6c 64 0a";
+            assert_eq!(Some((None, hello.clone())), find_dump(line));
+        }
+        assert_eq!(None, find_dump("12:00:01 status 10 20 30 40"));
+        assert_eq!(None, find_dump("booting kernel 5.10.0 at 0xdeadbeef"));
+    }
+
+    #[test]
+    fn scan_log() {
+        let log = r#"[    0.000000] Booting
+[    1.000000] 00000000: 68 65 6c 6c 6f 20 77 6f  hello wo
+[    1.000000] 00000008: 72 6c 64 0a              rld.
+[    1.100000] second dump follows
+IP 10.0.0.1.1234 > 10.0.0.2.80: Flags [S]
+	0x0000:  4500 003c 1c46 4000 4006 b1e6 ac10 0a63  E..<.F@.@......c
+	0x0010:  ffff ffff ffff ffff ffff ffff ffff ffff  ................
+*
+	0x0030:  0102                                     ..
+done
+"#;
+        let blobs: Vec<Blob> = Scanner::new(log.as_bytes())
+            .map(|blob| blob.unwrap())
+            .collect();
+        assert_eq!(2, blobs.len());
+        assert_eq!(
+            Blob {
+                first_line: 2,
+                last_line: 3,
+                address: 0,
+                data: b"hello world\n".to_vec()
+            },
+            blobs[0]
+        );
+        assert_eq!(
+            (6, 9, 0),
+            (blobs[1].first_line, blobs[1].last_line, blobs[1].address)
+        );
+        let mut expected = vec![
+            0x45, 0x00, 0x00, 0x3c, 0x1c, 0x46, 0x40, 0x00, 0x40, 0x06, 0xb1, 0xe6, 0xac, 0x10,
+            0x0a, 0x63,
+        ];
+        expected.extend(vec![0xff; 32]);
+        expected.extend(vec![0x01, 0x02]);
+        assert_eq!(expected, blobs[1].data);
+    }
+
+    #[test]
+    fn scan_splits_dumps_with_discontinuous_addresses() {
+        let log = "00000000: 0102 0304  ....\n00000000: 0506 0708  ....\n";
+        let blobs: Vec<Blob> = Scanner::new(log.as_bytes())
+            .map(|blob| blob.unwrap())
+            .collect();
+        assert_eq!(2, blobs.len());
+        assert_eq!(vec![1, 2, 3, 4], blobs[0].data);
+        assert_eq!(vec![5, 6, 7, 8], blobs[1].data);
+    }
+
+    #[test]
+    fn hex_text_parser() {
+        let expected = vec![0xDE, 0xAD, 0xBE, 0xEF];
+        assert_eq!(expected, hex_text("DE AD be ef").unwrap());
+        assert_eq!(expected, hex_text("deadbeef\n").unwrap());
+        assert_eq!(expected, hex_text("0xDE,0xAD, 0xBE,\n0XEF").unwrap());
+        assert_eq!(expected, hex_text("\\xde\\xad\\xbe\\xef").unwrap());
+        assert_eq!(expected, hex_text("de:ad:be:ef").unwrap());
+        assert_eq!(vec![0x0A, 0x01], hex_text("0xA, 0x1").unwrap());
+        assert_eq!(Vec::<u8>::new(), hex_text(" \n").unwrap());
+        assert_eq!(
+            "line 2, column 5: expected hex digit, found 'G'",
+            hex_text("dead\n0xbeGf").unwrap_err().to_string()
+        );
+        assert_eq!(
+            "line 1, column 4: odd number of hex digits in 'abc'",
+            hex_text("de abc").unwrap_err().to_string()
+        );
     }
 }
@@ -0,0 +1,326 @@
+//! The diff module contains code related to comparing the dumps of multiple inputs.
+use crate::convert::{parse_number, ParseError};
+use crate::dump::{visible_width, Config, OutputLine};
+use std::collections::VecDeque;
+use std::io::Write;
+use std::ops::Range;
+
+/// Parses a mask, the address ranges it contains are ignored when inputs are compared.
+///
+/// Every line contains one range, either `<start>-<end>` (the end is exclusive) or
+/// `<start>+<length>`. Numbers are decimal or hex prefixed with `0x`, everything following a `#`
+/// is a comment.
+pub fn parse_mask(text: &str) -> Result<Vec<Range<u64>>, ParseError> {
+    let mut mask = Vec::new();
+    for (index, line) in text.lines().enumerate() {
+        let range = line.split('#').next().unwrap_or("").trim();
+        if range.is_empty() {
+            continue;
+        }
+        let error = |message: &str| ParseError::Syntax {
+            line: index + 1,
+            column: line.find(|c: char| !c.is_whitespace()).unwrap_or(0) + 1,
+            message: format!("{} '{}'", message, range),
+        };
+        let (start, end) = if let Some((start, end)) = range.split_once('-') {
+            (parse_number(start).ok(), parse_number(end).ok())
+        } else if let Some((start, length)) = range.split_once('+') {
+            let start = parse_number(start).ok();
+            let length = parse_number(length).ok();
+            (start, start.zip(length).and_then(|(s, l)| s.checked_add(l)))
+        } else {
+            return Err(error("expected <start>-<end> or <start>+<length>, found"));
+        };
+        match (start, end) {
+            (Some(start), Some(end)) if start <= end => mask.push(start..end),
+            _ => return Err(error("invalid range")),
+        }
+    }
+    Ok(mask)
+}
+
+/// Result of the comparison of inputs.
+#[derive(Debug, Default, PartialEq)]
+pub struct Summary {
+    /// Amount of positions at which at least one input differs from the first one.
+    pub differing_bytes: u64,
+    /// Amount of bits which differ from the first input, summed up over all other inputs.
+    pub differing_bits: u64,
+    /// Size of every input.
+    pub sizes: Vec<u64>,
+}
+
+impl Summary {
+    pub fn is_identical(&self) -> bool {
+        self.differing_bytes == 0 && self.sizes.windows(2).all(|pair| pair[0] == pair[1])
+    }
+}
+
+/// A single line of all inputs.
+struct Line {
+    offset: u64,
+    data: Vec<Vec<u8>>,
+    highlight: Vec<bool>,
+}
+
+/// The `Diff` struct contains all settings needed to compare inputs side by side.
+#[derive(Debug, Clone)]
+pub struct Diff {
+    output_settings: Config,
+    context: Option<usize>,
+    mask: Vec<Range<u64>>,
+}
+
+impl Diff {
+    pub fn new(output_settings: Config) -> Diff {
+        Diff {
+            output_settings,
+            context: Some(1),
+            mask: Vec::new(),
+        }
+    }
+
+    /// Sets the amount of unchanged lines shown around changed ones, `None` shows all lines.
+    pub fn context(mut self, context: Option<usize>) -> Self {
+        self.context = context;
+        self
+    }
+
+    /// Sets the address ranges which are ignored by the comparison.
+    pub fn mask(mut self, mask: Vec<Range<u64>>) -> Self {
+        self.mask = mask;
+        self
+    }
+
+    /// Dumps `inputs` side by side and highlights the bytes which differ from the first input.
+    ///
+    /// Differing bytes are colored if colors are enabled, otherwise they are marked by a line of
+    /// `^` below. Groups of shown lines are separated by `--`.
+    pub fn compare<I>(
+        &self,
+        inputs: Vec<I>,
+        writer: &mut dyn Write,
+    ) -> Result<Summary, anyhow::Error>
+    where
+        I: Iterator<Item = u8>,
+    {
+        let bytes_per_line = self.output_settings.bytes_per_line();
+        let mut inputs = inputs;
+        let mut summary = Summary {
+            sizes: vec![0; inputs.len()],
+            ..Summary::default()
+        };
+        let mut offset = 0;
+        // unchanged lines which are shown if a change follows
+        let mut pending: VecDeque<Line> = VecDeque::new();
+        let mut trailing = 0;
+        let mut skipped = false;
+        let mut shown_any = false;
+        loop {
+            let data: Vec<Vec<u8>> = inputs
+                .iter_mut()
+                .map(|input| input.by_ref().take(bytes_per_line).collect())
+                .collect();
+            if data.iter().all(Vec::is_empty) {
+                break;
+            }
+            for (size, data) in summary.sizes.iter_mut().zip(&data) {
+                *size += data.len() as u64;
+            }
+            let highlight = self.compare_line(offset, &data, &mut summary);
+            let line = Line {
+                offset,
+                data,
+                highlight,
+            };
+            offset += bytes_per_line as u64;
+            let context = match self.context {
+                Some(context) => context,
+                None => {
+                    self.write_line(writer, &line)?;
+                    continue;
+                }
+            };
+            if line.highlight.contains(&true) {
+                if skipped && shown_any {
+                    writeln!(writer, "--")?;
+                }
+                for line in pending.drain(..) {
+                    self.write_line(writer, &line)?;
+                }
+                self.write_line(writer, &line)?;
+                shown_any = true;
+                skipped = false;
+                trailing = context;
+            } else if trailing > 0 {
+                self.write_line(writer, &line)?;
+                trailing -= 1;
+            } else {
+                pending.push_back(line);
+                if pending.len() > context {
+                    pending.pop_front();
+                    skipped = true;
+                }
+            }
+        }
+        Ok(summary)
+    }
+
+    /// Determines which bytes of a line differ, bytes missing in some inputs differ as well.
+    fn compare_line(&self, offset: u64, data: &[Vec<u8>], summary: &mut Summary) -> Vec<bool> {
+        let length = data.iter().map(Vec::len).max().unwrap_or(0);
+        let mut highlight = vec![false; length];
+        for (index, differs) in highlight.iter_mut().enumerate() {
+            let address = self.output_settings.start_address() + offset + index as u64;
+            if self.mask.iter().any(|range| range.contains(&address)) {
+                continue;
+            }
+            let first = data[0].get(index);
+            for other in data[1..].iter().map(|data| data.get(index)) {
+                match (first, other) {
+                    (Some(a), Some(b)) if a != b => {
+                        *differs = true;
+                        summary.differing_bits += (a ^ b).count_ones() as u64;
+                    }
+                    (Some(_), None) | (None, Some(_)) => *differs = true,
+                    _ => {}
+                }
+            }
+            if *differs {
+                summary.differing_bytes += 1;
+            }
+        }
+        highlight
+    }
+
+    fn write_line(&self, writer: &mut dyn Write, line: &Line) -> Result<(), anyhow::Error> {
+        let settings = self
+            .output_settings
+            .set_address(self.output_settings.start_address() + line.offset);
+        let mut text = String::new();
+        let mut markers = String::new();
+        for (index, data) in line.data.iter().enumerate() {
+            let settings = if index == 0 {
+                settings
+            } else {
+                text += " | ";
+                markers += "   ";
+                settings.show_address(false)
+            };
+            let output_line = || {
+                OutputLine::new(data)
+                    .format(settings)
+                    .highlight(&line.highlight)
+            };
+            let pane = output_line().to_string();
+            let pane_markers = output_line().markers().to_string();
+            text += &pane;
+            markers += &pane_markers;
+            if index + 1 < line.data.len() {
+                // a shorter line is padded, so the next input lines up
+                let width = settings.line_width();
+                text += &" ".repeat(width.saturating_sub(visible_width(&pane)));
+                markers += &" ".repeat(width.saturating_sub(visible_width(&pane_markers)));
+            }
+        }
+        writeln!(writer, "{}", text)?;
+        if !self.output_settings.uses_color() && line.highlight.contains(&true) {
+            writeln!(writer, "{}", markers.trim_end())?;
+        }
+        Ok(())
+    }
+}
+
+#[cfg(test)]
+mod test {
+
+    use super::*;
+    use crate::dump::ControlStyle;
+
+    fn compare(diff: Diff, inputs: &[&[u8]]) -> (String, Summary) {
+        let inputs = inputs.iter().map(|input| input.iter().cloned()).collect();
+        let mut output = Vec::new();
+        let summary = diff.compare(inputs, &mut output).unwrap();
+        (String::from_utf8(output).unwrap(), summary)
+    }
+
+    #[test]
+    fn parse_mask_ranges() {
+        let mask = "# checksum and build time\n0x10-0x14\n\n32+4 # timestamp\n";
+        assert_eq!(vec![0x10..0x14, 32..36], parse_mask(mask).unwrap());
+        let error = parse_mask("0x10-0x14\n  0x20").unwrap_err().to_string();
+        assert_eq!(
+            "line 2, column 3: expected <start>-<end> or <start>+<length>, found '0x20'",
+            error
+        );
+        assert!(parse_mask("0x20-0x10").is_err());
+        let error = parse_mask("0xffffffffffffffff+2").unwrap_err().to_string();
+        assert_eq!(
+            "line 1, column 1: invalid range '0xffffffffffffffff+2'",
+            error
+        );
+    }
+
+    #[test]
+    fn marks_differing_bytes() {
+        let diff = Diff::new(Config::new().columns(4));
+        let (output, summary) = compare(diff, &[b"abcd", b"abed"]);
+        let expected_output = format!(
+            "00000000: 61 62 63 64  abcd | 61 62 65 64  abed\n{}^^{}^{}^^{}^\n",
+            " ".repeat(16),
+            " ".repeat(7),
+            " ".repeat(10),
+            " ".repeat(7)
+        );
+        assert_eq!(expected_output, output);
+        assert_eq!(1, summary.differing_bytes);
+        assert_eq!(2, summary.differing_bits);
+        assert!(!summary.is_identical());
+    }
+
+    #[test]
+    fn shows_changed_lines_with_context() {
+        let a = [0u8; 40];
+        let mut b = [0u8; 40];
+        b[0] = 1;
+        b[36] = 1;
+        let diff = Diff::new(Config::new().columns(4).show_interpretation(false));
+        let (output, summary) = compare(diff, &[&a, &b]);
+        let addresses: Vec<&str> = output
+            .lines()
+            .filter(|line| !line.trim_start().starts_with('^'))
+            .map(|line| line.split(':').next().unwrap())
+            .collect();
+        assert_eq!(
+            vec!["00000000", "00000004", "--", "00000020", "00000024"],
+            addresses
+        );
+        assert_eq!(2, summary.differing_bytes);
+    }
+
+    #[test]
+    fn masked_and_missing_bytes() {
+        let diff = Diff::new(Config::new().columns(4)).mask(parse_mask("1+1").unwrap());
+        let (output, summary) = compare(diff, &[b"abc", b"aXcd"]);
+        assert_eq!(1, summary.differing_bytes);
+        assert_eq!(0, summary.differing_bits);
+        assert_eq!(vec![3, 4], summary.sizes);
+        assert!(output.lines().nth(1).unwrap().ends_with('^'));
+        let (output, summary) = compare(Diff::new(Config::new()), &[b"abc", b"abc"]);
+        assert_eq!("", output);
+        assert!(summary.is_identical());
+    }
+
+    #[test]
+    fn panes_line_up_after_a_shorter_line() {
+        let config = Config::new().columns(4).show_interpretation(false);
+        let (output, _) = compare(Diff::new(config), &[b"ab", b"abcd"]);
+        let lines: Vec<&str> = output.lines().collect();
+        assert_eq!("00000000: 61 62        | 61 62 63 64 ", lines[0]);
+        assert_eq!(lines[0].find("63 64"), lines[1].find("^^"));
+        let config = Config::new().columns(4).control_style(ControlStyle::Caret);
+        let (output, _) = compare(Diff::new(config), &[b"\x01\x02", b"\x01\x02cd"]);
+        let expected_line = "00000000: 01 02        ^A^B     | 01 02 63 64  ^A^Bcd";
+        assert_eq!(expected_line, output.lines().next().unwrap());
+    }
+}
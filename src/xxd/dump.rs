@@ -1,4 +1,7 @@
 //! The dump module contains code related for outputing/dumping data.
+use crate::annotate::Mark;
+use crate::codepage::{is_wide, CodePage, PLACEHOLDER};
+use std::convert::TryInto;
 use std::fmt;
 use std::io::Write;
 use std::iter::Iterator;
@@ -26,15 +29,333 @@ impl From<String> for Format {
     }
 }
 
+impl Format {
+    /// Amount of characters needed to output a single byte.
+    pub fn width(&self) -> usize {
+        match self {
+            Format::HexUpperCase => 2,
+            Format::Hex => 2,
+            Format::Octal => 3,
+            Format::Decimal => 3,
+            Format::Binary => 8,
+        }
+    }
+
+    /// Base of the number system used to output a byte.
+    pub fn radix(&self) -> u32 {
+        match self {
+            Format::HexUpperCase => 16,
+            Format::Hex => 16,
+            Format::Octal => 8,
+            Format::Decimal => 10,
+            Format::Binary => 2,
+        }
+    }
+
+    /// Formats a single byte, zero padded to `width()` characters.
+    pub fn format(&self, byte: u8) -> String {
+        match self {
+            Format::HexUpperCase => format!("{:02.X}", byte),
+            Format::Hex => format!("{:02.x}", byte),
+            Format::Octal => format!("{:03.o}", byte),
+            Format::Decimal => format!("{:03}", byte),
+            Format::Binary => format!("{:08b}", byte),
+        }
+    }
+}
+
+/// Enum which provides all possible formats of the address column.
+#[derive(Debug, PartialEq, Clone, Copy)]
+pub enum AddressFormat {
+    HexUpperCase,
+    Hex,
+    Decimal,
+    Octal,
+}
+
+impl From<String> for AddressFormat {
+    fn from(format_string: String) -> Self {
+        match format_string.as_ref() {
+            "Hex" => AddressFormat::HexUpperCase,
+            "hex" => AddressFormat::Hex,
+            "dec" => AddressFormat::Decimal,
+            "oct" => AddressFormat::Octal,
+            _ => panic!("Invalid address format"),
+        }
+    }
+}
+
+impl From<Format> for AddressFormat {
+    /// Hex values are accompanied by hex addresses of the same case, all other formats use
+    /// uppercase hex addresses.
+    fn from(format: Format) -> Self {
+        match format {
+            Format::Hex => AddressFormat::Hex,
+            _ => AddressFormat::HexUpperCase,
+        }
+    }
+}
+
+impl AddressFormat {
+    /// Amount of digits needed to output `address` in this format.
+    pub fn digits(&self, address: u64) -> usize {
+        match self {
+            AddressFormat::HexUpperCase | AddressFormat::Hex => format!("{:x}", address).len(),
+            AddressFormat::Decimal => address.to_string().len(),
+            AddressFormat::Octal => format!("{:o}", address).len(),
+        }
+    }
+
+    /// Radix of the digits of an address in this format.
+    pub fn radix(&self) -> u32 {
+        match self {
+            AddressFormat::HexUpperCase | AddressFormat::Hex => 16,
+            AddressFormat::Decimal => 10,
+            AddressFormat::Octal => 8,
+        }
+    }
+
+    fn format(&self, address: u64, width: usize) -> String {
+        match self {
+            AddressFormat::HexUpperCase => format!("{:01$X}", address, width),
+            AddressFormat::Hex => format!("{:01$x}", address, width),
+            AddressFormat::Decimal => format!("{:01$}", address, width),
+            AddressFormat::Octal => format!("{:01$o}", address, width),
+        }
+    }
+}
+
+/// Enum which provides the types the values pane can decode the data as.
+#[derive(Debug, PartialEq, Clone, Copy)]
+pub enum ValueType {
+    U8,
+    I8,
+    U16,
+    I16,
+    U32,
+    I32,
+    U64,
+    I64,
+    F32,
+    F64,
+}
+
+impl From<String> for ValueType {
+    fn from(type_string: String) -> Self {
+        match type_string.as_ref() {
+            "u8" => ValueType::U8,
+            "i8" => ValueType::I8,
+            "u16" => ValueType::U16,
+            "i16" => ValueType::I16,
+            "u32" => ValueType::U32,
+            "i32" => ValueType::I32,
+            "u64" => ValueType::U64,
+            "i64" => ValueType::I64,
+            "f32" => ValueType::F32,
+            "f64" => ValueType::F64,
+            _ => panic!("Invalid value type"),
+        }
+    }
+}
+
+macro_rules! decode {
+    ($type:ty, $bytes:expr, $endianness:expr) => {{
+        let bytes = $bytes.try_into().unwrap();
+        match $endianness {
+            Endianness::Big => <$type>::from_be_bytes(bytes),
+            Endianness::Little => <$type>::from_le_bytes(bytes),
+        }
+    }};
+}
+
+impl ValueType {
+    /// Amount of bytes a single value is encoded in.
+    pub fn size(&self) -> usize {
+        match self {
+            ValueType::U8 | ValueType::I8 => 1,
+            ValueType::U16 | ValueType::I16 => 2,
+            ValueType::U32 | ValueType::I32 | ValueType::F32 => 4,
+            ValueType::U64 | ValueType::I64 | ValueType::F64 => 8,
+        }
+    }
+
+    /// Amount of characters needed to output the widest value.
+    pub fn width(&self) -> usize {
+        match self {
+            ValueType::U8 => 3,
+            ValueType::I8 => 4,
+            ValueType::U16 => 5,
+            ValueType::I16 => 6,
+            ValueType::U32 => 10,
+            ValueType::I32 => 11,
+            ValueType::U64 | ValueType::I64 => 20,
+            ValueType::F32 => 14,
+            ValueType::F64 => 24,
+        }
+    }
+
+    /// Formats the value encoded in `bytes`, which must contain exactly `size()` bytes.
+    pub fn format(&self, bytes: &[u8], endianness: Endianness) -> String {
+        match self {
+            ValueType::U8 => bytes[0].to_string(),
+            ValueType::I8 => (bytes[0] as i8).to_string(),
+            ValueType::U16 => decode!(u16, bytes, endianness).to_string(),
+            ValueType::I16 => decode!(i16, bytes, endianness).to_string(),
+            ValueType::U32 => decode!(u32, bytes, endianness).to_string(),
+            ValueType::I32 => decode!(i32, bytes, endianness).to_string(),
+            ValueType::U64 => decode!(u64, bytes, endianness).to_string(),
+            ValueType::I64 => decode!(i64, bytes, endianness).to_string(),
+            ValueType::F32 => format_float(decode!(f32, bytes, endianness)),
+            ValueType::F64 => format_float(decode!(f64, bytes, endianness)),
+        }
+    }
+}
+
+/// Formats very large and very small floats in scientific notation, so they fit into the width
+/// of their `ValueType`.
+fn format_float<T>(value: T) -> String
+where
+    T: fmt::Display + fmt::LowerExp + Into<f64> + Copy,
+{
+    let magnitude = value.into().abs();
+    if magnitude == 0.0 || !magnitude.is_finite() || (1e-4..1e7).contains(&magnitude) {
+        value.to_string()
+    } else {
+        format!("{:e}", value)
+    }
+}
+
+/// Enum which provides the byte orders in which the bytes of a group can be shown.
+#[derive(Debug, PartialEq, Clone, Copy)]
+pub enum Endianness {
+    Big,
+    Little,
+}
+
+/// Enum which provides the classes bytes are grouped in for colored output.
+#[derive(Debug, PartialEq, Clone, Copy)]
+pub enum ByteClass {
+    Null,
+    AsciiPrintable,
+    AsciiWhitespace,
+    AsciiControl,
+    NonAscii,
+}
+
+impl From<u8> for ByteClass {
+    fn from(byte: u8) -> Self {
+        match byte {
+            0x00 => ByteClass::Null,
+            b' ' | b'\t' | b'\n' | 0x0b | 0x0c | b'\r' => ByteClass::AsciiWhitespace,
+            0x21..=0x7e => ByteClass::AsciiPrintable,
+            0x01..=0x7f => ByteClass::AsciiControl,
+            _ => ByteClass::NonAscii,
+        }
+    }
+}
+
+impl ByteClass {
+    /// ANSI (SGR) color code used for bytes of this class.
+    pub fn ansi_color(&self) -> u8 {
+        match self {
+            ByteClass::Null => 90,
+            ByteClass::AsciiPrintable => 36,
+            ByteClass::AsciiWhitespace => 32,
+            ByteClass::AsciiControl => 35,
+            ByteClass::NonAscii => 33,
+        }
+    }
+
+    /// CSS class used for bytes of this class in HTML output.
+    pub fn css_class(&self) -> &'static str {
+        match self {
+            ByteClass::Null => "null",
+            ByteClass::AsciiPrintable => "printable",
+            ByteClass::AsciiWhitespace => "whitespace",
+            ByteClass::AsciiControl => "control",
+            ByteClass::NonAscii => "non-ascii",
+        }
+    }
+}
+
+/// Enum which provides the styles in which control characters are shown in the interpretation
+/// column.
+#[derive(Debug, PartialEq, Clone, Copy)]
+pub enum ControlStyle {
+    /// `.` for every control character, like xxd
+    Dot,
+    /// Unicode control pictures, e.g. `␀`, `␊`, `␍`
+    Picture,
+    /// Caret notation, e.g. `^@`, `^J`, `^M`
+    Caret,
+    /// Symbols by byte class like hexyl: `⋄` NUL, `_` whitespace, `▪` other controls and `×` non
+    /// ASCII bytes without a character. Unlike hexyl, other controls don't use `•`, which marks
+    /// the continuation bytes of a character (see `crate::codepage::CONTINUATION`).
+    Symbol,
+}
+
+impl From<String> for ControlStyle {
+    fn from(style: String) -> Self {
+        match style.as_ref() {
+            "dot" => ControlStyle::Dot,
+            "picture" => ControlStyle::Picture,
+            "caret" => ControlStyle::Caret,
+            "symbol" => ControlStyle::Symbol,
+            _ => panic!("Invalid control character style"),
+        }
+    }
+}
+
+impl ControlStyle {
+    /// Writes the ASCII control character `control` (`0x00..=0x1f`, `0x7f`) in this style.
+    fn write_control(&self, f: &mut fmt::Formatter, control: char) -> fmt::Result {
+        match self {
+            ControlStyle::Dot => write!(f, "{}", PLACEHOLDER),
+            ControlStyle::Picture if control == '\u{7f}' => write!(f, "\u{2421}"),
+            ControlStyle::Picture => {
+                let picture = std::char::from_u32(0x2400 + control as u32).unwrap_or(PLACEHOLDER);
+                write!(f, "{}", picture)
+            }
+            ControlStyle::Caret => write!(f, "^{}", (control as u8 ^ 0x40) as char),
+            ControlStyle::Symbol => match ByteClass::from(control as u8) {
+                ByteClass::Null => write!(f, "⋄"),
+                ByteClass::AsciiWhitespace => write!(f, "_"),
+                _ => write!(f, "▪"),
+            },
+        }
+    }
+}
+
+/// Maximum amount of panes showing the bytes in different formats (see `Config::formats`).
+pub const MAX_PANES: usize = 5;
+
+/// Shown in place of the address in front of a ruler (see `Config::ruler`).
+pub const RULER_MARKER: &str = "#";
+
 #[derive(Debug, Clone, Copy)]
 pub struct Config {
-    start_address: usize,
+    start_address: u64,
+    display_offset: u64,
     show_address: bool,
+    show_file_offset: bool,
+    address_width: usize,
+    address_fmt: Option<AddressFormat>,
     group_size: usize,
     columns: usize,
     show_interpretation: bool,
     use_separator: bool,
     output_fmt: Format,
+    /// Formats of the panes following the first one, which uses `output_fmt`
+    more_formats: [Option<Format>; MAX_PANES - 1],
+    endianness: Endianness,
+    color: bool,
+    autoskip: bool,
+    code_page: CodePage,
+    control_style: ControlStyle,
+    show_bytes: bool,
+    value_type: Option<ValueType>,
+    html: bool,
+    ruler: Option<usize>,
 }
 
 impl Default for Config {
@@ -47,12 +368,26 @@ impl Config {
     pub fn new() -> Config {
         Config {
             start_address: 0,
+            display_offset: 0,
             show_address: true,
+            show_file_offset: false,
+            address_width: 8,
+            address_fmt: None,
             group_size: 1,
             columns: 8,
             show_interpretation: true,
             use_separator: true,
             output_fmt: Format::HexUpperCase,
+            more_formats: [None; MAX_PANES - 1],
+            endianness: Endianness::Big,
+            color: false,
+            autoskip: false,
+            code_page: CodePage::Ascii,
+            control_style: ControlStyle::Dot,
+            show_bytes: true,
+            value_type: None,
+            html: false,
+            ruler: None,
         }
     }
 
@@ -60,20 +395,72 @@ impl Config {
         self.columns * self.group_size
     }
 
-    pub fn start_address(&self) -> usize {
+    pub fn start_address(&self) -> u64 {
         self.start_address
     }
 
-    pub fn set_address(mut self, address: usize) -> Self {
+    pub fn uses_color(&self) -> bool {
+        self.color
+    }
+
+    /// Amount of terminal columns taken by a complete line. In caret style the interpretation
+    /// column needs up to two columns per byte.
+    pub(crate) fn line_width(&self) -> usize {
+        let data = vec![b'.'; self.bytes_per_line()];
+        let line = OutputLine::new(&data).format(self.color(false)).to_string();
+        let carets = self.show_interpretation && self.control_style == ControlStyle::Caret;
+        visible_width(&line) + if carets { self.bytes_per_line() } else { 0 }
+    }
+
+    /// Format of the address column, by default it follows the format of the values.
+    fn address_fmt(&self) -> AddressFormat {
+        self.address_fmt
+            .unwrap_or_else(|| AddressFormat::from(self.output_fmt))
+    }
+
+    pub fn set_address(mut self, address: u64) -> Self {
         self.start_address = address;
         self
     }
 
+    /// Sets an offset which is added to every shown address, e.g. the load address of an image.
+    pub fn display_offset(mut self, offset: u64) -> Self {
+        self.display_offset = offset;
+        self
+    }
+
     pub fn show_address(mut self, show: bool) -> Self {
         self.show_address = show;
         self
     }
 
+    /// Shows the offset within the input in front of the address which includes the display
+    /// offset.
+    pub fn show_file_offset(mut self, show: bool) -> Self {
+        self.show_file_offset = show;
+        self
+    }
+
+    /// Sets the minimum amount of digits of an address, larger addresses are never truncated.
+    pub fn address_width(mut self, width: usize) -> Self {
+        self.address_width = width;
+        self
+    }
+
+    pub fn address_format(mut self, fmt: AddressFormat) -> Self {
+        self.address_fmt = Some(fmt);
+        self
+    }
+
+    /// Widens the address column so every address up to `last_address` has the same width.
+    pub fn fit_address(mut self, last_address: u64) -> Self {
+        let digits = self
+            .address_fmt()
+            .digits(last_address.wrapping_add(self.display_offset));
+        self.address_width = std::cmp::max(self.address_width, digits);
+        self
+    }
+
     pub fn group_size(mut self, size: usize) -> Self {
         self.group_size = size;
         self
@@ -96,6 +483,102 @@ impl Config {
 
     pub fn format(mut self, fmt: Format) -> Self {
         self.output_fmt = fmt;
+        self.more_formats = [None; MAX_PANES - 1];
+        self
+    }
+
+    /// Shows the bytes in one pane per format, side by side. The first format is used like the one
+    /// set by `format`.
+    ///
+    /// # Panics
+    ///
+    /// Panics if more than `MAX_PANES` formats are given.
+    pub fn formats(mut self, formats: &[Format]) -> Self {
+        assert!(
+            formats.len() <= MAX_PANES,
+            "Invalid amount of formats, at most {} panes are supported",
+            MAX_PANES
+        );
+        if let Some((first, more)) = formats.split_first() {
+            self = self.format(*first);
+            for (pane, fmt) in self.more_formats.iter_mut().zip(more) {
+                *pane = Some(*fmt);
+            }
+        }
+        self
+    }
+
+    fn panes(&self) -> impl Iterator<Item = Format> + '_ {
+        std::iter::once(self.output_fmt).chain(self.more_formats.iter().flatten().cloned())
+    }
+
+    /// Sets the byte order in which the bytes of a group are shown.
+    ///
+    /// The interpretation column always shows the bytes in their original order.
+    pub fn endianness(mut self, endianness: Endianness) -> Self {
+        self.endianness = endianness;
+        self
+    }
+
+    /// Enables ANSI colors, every byte is colored according to its `ByteClass`.
+    pub fn color(mut self, color: bool) -> Self {
+        self.color = color;
+        self
+    }
+
+    /// Collapses runs of identical lines into a single `*` line.
+    ///
+    /// Only takes effect when addresses are shown, as the address of the next line is needed to
+    /// expand the `*` again.
+    pub fn autoskip(mut self, autoskip: bool) -> Self {
+        self.autoskip = autoskip;
+        self
+    }
+
+    /// Sets the code page used to show the bytes in the interpretation column.
+    pub fn code_page(mut self, code_page: CodePage) -> Self {
+        self.code_page = code_page;
+        self
+    }
+
+    /// Sets the style in which control characters are shown in the interpretation column.
+    pub fn control_style(mut self, style: ControlStyle) -> Self {
+        self.control_style = style;
+        self
+    }
+
+    /// Shows the bytes in the output format, they can be hidden to show the values pane only.
+    pub fn show_bytes(mut self, show: bool) -> Self {
+        self.show_bytes = show;
+        self
+    }
+
+    /// Adds a pane which shows the data decoded as values of `value_type`, in the byte order set
+    /// by `endianness`. The values only line up with the groups of bytes if the group size is a
+    /// multiple of the size of a value.
+    pub fn values(mut self, value_type: Option<ValueType>) -> Self {
+        self.value_type = value_type;
+        self
+    }
+
+    /// Dumps a self-contained HTML page instead of text, colors are replaced by CSS classes.
+    ///
+    /// Every line can be linked by its address, e.g. `#0x1A0`, every byte has a tooltip with its
+    /// values and hovering a byte highlights it in all columns.
+    pub fn html(mut self, html: bool) -> Self {
+        self.html = html;
+        self
+    }
+
+    /// Shows a ruler above the dump, which labels every column with its offset within the line.
+    ///
+    /// The ruler is repeated every `interval` lines, an interval of 0 shows it only once. A `#`
+    /// takes the place of the address, so the ruler is skipped when the dump is reverted.
+    ///
+    /// The interpretation column isn't labelled in `ControlStyle::Caret`, where the width of a
+    /// character depends on the byte.
+    pub fn ruler(mut self, interval: Option<usize>) -> Self {
+        self.ruler = interval;
         self
     }
 }
@@ -105,6 +588,12 @@ impl Config {
 pub struct OutputLine<'a> {
     output_settings: Config,
     data: &'a [u8],
+    before: &'a [u8],
+    after: &'a [u8],
+    highlight: &'a [bool],
+    marks: &'a [Option<Mark>],
+    markers: bool,
+    ruler: bool,
 }
 
 impl<'a> OutputLine<'a> {
@@ -112,31 +601,141 @@ impl<'a> OutputLine<'a> {
         OutputLine {
             output_settings: Config::new(),
             data,
+            before: &[],
+            after: &[],
+            highlight: &[],
+            marks: &[],
+            markers: false,
+            ruler: false,
         }
     }
 
     pub fn format(self, settings: Config) -> Self {
         OutputLine {
             output_settings: settings,
-            data: self.data,
+            ..self
+        }
+    }
+
+    /// Sets the bytes surrounding the line, which are needed to interpret characters crossing the
+    /// line boundaries (see `CodePage::interpret`).
+    pub fn context(self, before: &'a [u8], after: &'a [u8]) -> Self {
+        OutputLine {
+            before,
+            after,
+            ..self
+        }
+    }
+
+    /// Highlights the bytes whose index is set in `highlight`, e.g. differences between two dumps.
+    pub fn highlight(self, highlight: &'a [bool]) -> Self {
+        OutputLine { highlight, ..self }
+    }
+
+    /// Marks the bytes of annotated regions, they are colored by the region or bracketed by
+    /// `markers`.
+    pub fn annotate(self, marks: &'a [Option<Mark>]) -> Self {
+        OutputLine { marks, ..self }
+    }
+
+    /// Shows `^` markers below the highlighted bytes instead of the line itself, the markers line
+    /// up with the line, so they can be used if colors aren't available.
+    pub fn markers(self) -> Self {
+        OutputLine {
+            markers: true,
+            ..self
         }
     }
 
+    /// Shows the offset of every byte within the line instead of the line itself (see
+    /// `Config::ruler`), the data only determines the amount of labelled bytes.
+    pub fn ruler(self) -> Self {
+        OutputLine {
+            ruler: true,
+            ..self
+        }
+    }
+
+    fn is_highlighted(&self, index: usize) -> bool {
+        self.highlight.get(index).cloned().unwrap_or(false)
+    }
+
+    fn mark(&self, index: usize) -> Option<Mark> {
+        self.marks.get(index).cloned().flatten()
+    }
+
+    /// Marker of the byte at `index` shown by `width` characters.
+    fn marker(&self, index: usize, width: usize) -> String {
+        match self.mark(index) {
+            _ if self.is_highlighted(index) => "^".repeat(width),
+            Some(mark) => mark.bracket(width),
+            None => " ".repeat(width),
+        }
+    }
+
+    /// Returns the address column without the trailing colon, preceded by the file offset if
+    /// it is shown.
+    pub fn address(&self) -> String {
+        let settings = &self.output_settings;
+        let address_fmt = settings.address_fmt();
+        let mut address = String::new();
+        if settings.show_file_offset {
+            address += &address_fmt.format(settings.start_address, settings.address_width);
+            address += " ";
+        }
+        address += &address_fmt.format(
+            settings.start_address.wrapping_add(settings.display_offset),
+            settings.address_width,
+        );
+        address
+    }
+
+    /// Id of the line in HTML output, the displayed address as upper case hex, e.g. `0x1A0`.
+    fn anchor(&self) -> String {
+        let settings = &self.output_settings;
+        format!(
+            "0x{:X}",
+            settings.start_address.wrapping_add(settings.display_offset)
+        )
+    }
+
     fn write_address(&self, f: &mut fmt::Formatter) -> Result<usize, anyhow::Error> {
-        write!(f, "{:08.X}: ", self.output_settings.start_address)?;
-        Ok(10)
+        let address = self.address();
+        if self.ruler {
+            write!(f, "{:1$}", RULER_MARKER, address.len() + 2)?;
+        } else if self.markers {
+            write!(f, "{:1$}", "", address.len() + 2)?;
+        } else if self.output_settings.html {
+            write!(f, "<a href=\"#{}\">{}</a>: ", self.anchor(), address)?;
+        } else {
+            write!(f, "{}: ", address)?;
+        }
+        Ok(address.len() + 2)
     }
 
-    fn write_bytes(&self, f: &mut fmt::Formatter) -> Result<usize, anyhow::Error> {
+    fn write_bytes(&self, f: &mut fmt::Formatter, format: Format) -> Result<usize, anyhow::Error> {
         let mut bytes_written = 0;
-        for (byte_offset, b) in self.data.iter().enumerate() {
-            let is_seperator_necessary = (byte_offset + 1) % self.output_settings.group_size == 0;
+        let group_size = self.output_settings.group_size;
+        for (group_index, group) in self.data.chunks(group_size).enumerate() {
+            let indices = group_index * group_size..group_index * group_size + group.len();
+            if self.output_settings.endianness == Endianness::Little {
+                // an incomplete group is aligned to the right, like the least significant bytes
+                // of a complete one
+                let missing = (group_size - group.len()) * format.width();
+                write!(f, "{:1$}", "", missing)?;
+                bytes_written += missing;
+                for index in indices.rev() {
+                    bytes_written += self.write_formated_byte(f, index, format)?;
+                }
+            } else {
+                for index in indices {
+                    bytes_written += self.write_formated_byte(f, index, format)?;
+                }
+            }
+            let is_seperator_necessary = group.len() == group_size;
             if is_seperator_necessary && self.output_settings.use_separator {
-                bytes_written += self.write_formated_byte(f, *b)?;
                 write!(f, " ")?;
                 bytes_written += 1;
-            } else {
-                bytes_written += self.write_formated_byte(f, *b)?;
             }
         }
         Ok(bytes_written)
@@ -145,67 +744,221 @@ impl<'a> OutputLine<'a> {
     fn write_formated_byte(
         &self,
         f: &mut fmt::Formatter,
-        byte: u8,
+        index: usize,
+        format: Format,
     ) -> Result<usize, anyhow::Error> {
-        match self.output_settings.output_fmt {
-            Format::HexUpperCase => {
-                write!(f, "{:02.X}", byte)?;
-                Ok(2)
-            }
-            Format::Hex => {
-                write!(f, "{:02.x}", byte)?;
-                Ok(2)
-            }
-            Format::Octal => {
-                write!(f, "{:03.o}", byte)?;
-                Ok(3)
-            }
-            Format::Decimal => {
-                write!(f, "{:03}", byte)?;
-                Ok(3)
-            }
-            Format::Binary => {
-                write!(f, "{:08b}", byte)?;
-                Ok(8)
-            }
+        if self.markers {
+            write!(f, "{}", self.marker(index, format.width()))?;
+            return Ok(format.width());
         }
+        if self.ruler {
+            write!(f, "{}", self.ruler_label(index, format.width()))?;
+            return Ok(format.width());
+        }
+        self.start_color(f, index)?;
+        write!(f, "{}", format.format(self.data[index]))?;
+        self.end_color(f)?;
+        Ok(format.width())
+    }
+
+    /// Label of the byte at `index` in a ruler, its offset in the format of the addresses right
+    /// aligned to `width` characters, only the last digits are kept if the offset doesn't fit.
+    fn ruler_label(&self, index: usize, width: usize) -> String {
+        let label = self.output_settings.address_fmt().format(index as u64, 2);
+        format!("{:>1$}", &label[label.len().saturating_sub(width)..], width)
+    }
+
+    /// Escape codes and markup aren't visible, therefore they are not accounted for in the written
+    /// length.
+    fn start_color(&self, f: &mut fmt::Formatter, index: usize) -> fmt::Result {
+        let byte = self.data[index];
+        if self.output_settings.html {
+            let highlight = if self.is_highlighted(index) {
+                " highlight"
+            } else {
+                ""
+            };
+            write!(
+                f,
+                "<span class=\"{}{}\" data-i=\"{}\" title=\"dec {}, oct {:o}, bin {:08b}\">",
+                ByteClass::from(byte).css_class(),
+                highlight,
+                index,
+                byte,
+                byte,
+                byte
+            )?;
+        } else if self.output_settings.color && self.is_highlighted(index) {
+            write!(f, "\x1b[7;31m")?;
+        } else if let (true, Some(mark)) = (self.output_settings.color, self.mark(index)) {
+            write!(f, "\x1b[30;{}m", mark.color.ansi_background())?;
+        } else if self.output_settings.color {
+            write!(f, "\x1b[{}m", ByteClass::from(byte).ansi_color())?;
+        }
+        Ok(())
+    }
+
+    fn end_color(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        if self.output_settings.html {
+            write!(f, "</span>")?;
+        } else if self.output_settings.color {
+            write!(f, "\x1b[0m")?;
+        }
+        Ok(())
+    }
+
+    /// Writes the complete values of the line right aligned, each followed by a space.
+    fn write_values(
+        &self,
+        f: &mut fmt::Formatter,
+        value_type: ValueType,
+    ) -> Result<usize, fmt::Error> {
+        let width = value_type.width();
+        let mut values_written = 0;
+        for (index, bytes) in self.data.chunks_exact(value_type.size()).enumerate() {
+            let value = if self.ruler {
+                self.ruler_label(index * value_type.size(), width)
+            } else {
+                value_type.format(bytes, self.output_settings.endianness)
+            };
+            write!(f, "{:>1$} ", value, width)?;
+            values_written += width + 1;
+        }
+        Ok(values_written)
     }
 
     fn write_interpretation(&self, f: &mut fmt::Formatter) -> Result<usize, anyhow::Error> {
         write!(f, " ")?;
-        for b in self.data.iter() {
-            match *b {
-                character @ 20u8..=126u8 => write!(f, "{}", character as char)?,
-                _ => write!(f, ".")?,
+        if self.markers {
+            for index in 0..self.data.len() {
+                write!(f, "{}", self.marker(index, 1))?;
             }
+            return Ok(self.data.len());
         }
+        if self.ruler {
+            // a caret takes a column of its own, the labels couldn't line up with every line
+            if self.output_settings.control_style != ControlStyle::Caret {
+                for index in 0..self.data.len() {
+                    write!(f, "{}", self.ruler_label(index, 1))?;
+                }
+            }
+            return Ok(self.data.len());
+        }
+        self.write_characters(f)?;
         Ok(self.data.len())
     }
-}
 
-impl<'a> fmt::Display for OutputLine<'a> {
-    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
-        let format_size = |fmt: Format| match fmt {
-            Format::HexUpperCase => 2,
-            Format::Hex => 2,
-            Format::Octal => 3,
-            Format::Decimal => 3,
-            Format::Binary => 8,
-        };
+    fn write_characters(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        let characters =
+            self.output_settings
+                .code_page
+                .interpret(self.before, self.data, self.after);
+        for (index, (b, character)) in self.data.iter().zip(characters).enumerate() {
+            self.start_color(f, index)?;
+            if self.output_settings.html {
+                write!(
+                    f,
+                    "{}",
+                    html_escape(&Character(self, *b, character).to_string())
+                )?;
+            } else {
+                self.write_character(f, *b, character)?;
+            }
+            self.end_color(f)?;
+        }
+        Ok(())
+    }
+
+    /// Writes the interpretation of `byte`, `character` is the character it decodes to.
+    fn write_character(&self, f: &mut fmt::Formatter, byte: u8, character: char) -> fmt::Result {
+        let style = self.output_settings.control_style;
+        match character {
+            '\u{0}'..='\u{1f}' | '\u{7f}' => style.write_control(f, character),
+            PLACEHOLDER if style == ControlStyle::Symbol && byte >= 0x80 => write!(f, "×"),
+            _ => write!(f, "{}", character),
+        }
+    }
+
+    /// Formats the bytes of every group in the first output format, like the first pane without
+    /// colors and padding.
+    pub fn cells(&self) -> Vec<String> {
+        let format = self.output_settings.output_fmt;
+        self.data
+            .chunks(self.output_settings.group_size)
+            .map(|group| {
+                if self.output_settings.endianness == Endianness::Little {
+                    group
+                        .iter()
+                        .rev()
+                        .map(|byte| format.format(*byte))
+                        .collect()
+                } else {
+                    group.iter().map(|byte| format.format(*byte)).collect()
+                }
+            })
+            .collect()
+    }
+
+    /// Returns the interpretation of every byte without colors, a control character may be shown
+    /// by more than one character (see `ControlStyle`).
+    pub fn characters(&self) -> Vec<String> {
+        let characters =
+            self.output_settings
+                .code_page
+                .interpret(self.before, self.data, self.after);
+        self.data
+            .iter()
+            .zip(characters)
+            .map(|(b, character)| Character(self, *b, character).to_string())
+            .collect()
+    }
+
+    /// Returns the interpretation column without colors.
+    pub fn interpretation(&self) -> String {
+        self.characters().concat()
+    }
+
+    fn write_columns(&self, f: &mut fmt::Formatter) -> fmt::Result {
         if self.output_settings.show_address {
             match self.write_address(f) {
                 Ok(_) => {}
                 Err(_) => return Err(std::fmt::Error),
             }
         }
-        let bytes_written = self.write_bytes(f).map_err(|_| ::std::fmt::Error)?;
-        let expected_length = self.output_settings.columns
-            * self.output_settings.group_size
-            * format_size(self.output_settings.output_fmt)
-            + (self.output_settings.columns);
-        let padding = expected_length - bytes_written;
-        for _ in 0..padding {
-            write!(f, " ")?;
+        if self.output_settings.show_bytes {
+            for (index, format) in self.output_settings.panes().enumerate() {
+                if index > 0 {
+                    // a double space separates the panes, so the first one can still be reverted
+                    write!(f, " ")?;
+                }
+                let bytes_written = self.write_bytes(f, format).map_err(|_| ::std::fmt::Error)?;
+                let expected_length =
+                    self.output_settings.columns * self.output_settings.group_size * format.width()
+                        + (self.output_settings.columns);
+                let padding = expected_length - bytes_written;
+                for _ in 0..padding {
+                    write!(f, " ")?;
+                }
+            }
+        }
+        if let Some(value_type) = self.output_settings.value_type {
+            if self.output_settings.show_bytes {
+                // a double space separates the panes, so the bytes can still be reverted
+                write!(f, " ")?;
+            }
+            let values_written = if self.markers {
+                0
+            } else {
+                self.write_values(f, value_type)?
+            };
+            let expected_length = self.output_settings.bytes_per_line() / value_type.size()
+                * (value_type.width() + 1);
+            write!(
+                f,
+                "{:1$}",
+                "",
+                expected_length.saturating_sub(values_written)
+            )?;
         }
         if self.output_settings.show_interpretation {
             match self.write_interpretation(f) {
@@ -217,6 +970,84 @@ impl<'a> fmt::Display for OutputLine<'a> {
     }
 }
 
+/// The interpretation of a byte and the character it decodes to.
+struct Character<'a, 'b>(&'b OutputLine<'a>, u8, char);
+
+impl<'a, 'b> fmt::Display for Character<'a, 'b> {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        self.0.write_character(f, self.1, self.2)
+    }
+}
+
+fn html_escape(text: &str) -> String {
+    text.replace('&', "&amp;")
+        .replace('<', "&lt;")
+        .replace('>', "&gt;")
+        .replace('"', "&quot;")
+}
+
+impl<'a> fmt::Display for OutputLine<'a> {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        if !self.output_settings.html || self.markers || self.ruler {
+            return self.write_columns(f);
+        }
+        if self.output_settings.show_address {
+            write!(f, "<span class=\"row\" id=\"{}\">", self.anchor())?;
+        } else {
+            write!(f, "<span class=\"row\">")?;
+        }
+        self.write_columns(f)?;
+        write!(f, "</span>")
+    }
+}
+
+/// Start of the HTML page written by `dump_iterator`, the lines follow in a `pre` element.
+const HTML_HEADER: &str = r#"<!DOCTYPE html>
+<html>
+<head>
+<meta charset="utf-8">
+<title>xxd-rs</title>
+<style>
+body { margin: 1em; }
+pre { font-family: monospace; }
+a { color: inherit; text-decoration: none; }
+.row:target { background: #fff3b0; }
+.null { color: #808080; }
+.printable { color: #008b8b; }
+.whitespace { color: #228b22; }
+.control { color: #8b008b; }
+.non-ascii { color: #b8860b; }
+.highlight { background: #ffb3b3; }
+.hover { background: #b3d4ff; }
+</style>
+</head>
+<body>
+<pre>
+"#;
+
+/// End of the HTML page, the script highlights a byte in all columns while it is hovered.
+const HTML_FOOTER: &str = r#"</pre>
+<script>
+function hover(event, on) {
+  var index = event.target.getAttribute("data-i");
+  if (index === null) {
+    return;
+  }
+  var cells = event.currentTarget.querySelectorAll('[data-i="' + index + '"]');
+  for (var i = 0; i < cells.length; i++) {
+    cells[i].classList.toggle("hover", on);
+  }
+}
+var rows = document.querySelectorAll(".row");
+for (var i = 0; i < rows.length; i++) {
+  rows[i].addEventListener("mouseover", function (event) { hover(event, true); });
+  rows[i].addEventListener("mouseout", function (event) { hover(event, false); });
+}
+</script>
+</body>
+</html>
+"#;
+
 // try static dispatch by changing params -> accept gernic with trait bounds e.g. into_iter
 pub fn dump_iterator<I>(
     sequence: I,
@@ -226,37 +1057,184 @@ pub fn dump_iterator<I>(
 where
     I: Iterator<Item = u8>,
 {
+    if output_settings.html {
+        writer.write_all(HTML_HEADER.as_bytes())?;
+    }
+    let mut lines = LineWriter::new(writer, output_settings);
+    for_each_line(sequence, output_settings, |chunk| lines.write(chunk))?;
+    lines.finish()?;
+    if output_settings.html {
+        writer.write_all(HTML_FOOTER.as_bytes())?;
+    }
+    Ok(())
+}
+
+/// Writes `data` as a single line of a dump, the tests use it to check the layout of a line.
+#[cfg(test)]
+fn dump_line(data: &[u8], writer: &mut dyn Write, output_settings: Config) {
+    let chunk = Chunk {
+        offset: 0,
+        before: &[],
+        data,
+        after: &[],
+    };
+    LineWriter::new(writer, output_settings)
+        .write(chunk)
+        .unwrap();
+}
+
+/// Amount of terminal columns taken by `text`, ANSI escape sequences take none.
+pub(crate) fn visible_width(text: &str) -> usize {
+    let mut width = 0;
+    let mut characters = text.chars();
+    while let Some(character) = characters.next() {
+        if character == '\x1b' {
+            // skip the sequence up to its final letter
+            characters.find(char::is_ascii_alphabetic);
+        } else if is_wide(character) {
+            width += 2;
+        } else {
+            width += 1;
+        }
+    }
+    width
+}
+
+/// A line of the input along with the bytes surrounding it.
+pub(crate) struct Chunk<'a> {
+    /// Offset of the line relative to the start of the input.
+    pub offset: u64,
+    /// Last bytes before the line, starting at an even offset (see `CodePage::interpret`).
+    pub before: &'a [u8],
+    pub data: &'a [u8],
+    /// Bytes following the line, as many as the code page needs to look ahead.
+    pub after: &'a [u8],
+}
+
+/// Splits `sequence` into lines of `bytes_per_line()` bytes and passes them to `f` in order.
+pub(crate) fn for_each_line<I, F>(
+    sequence: I,
+    output_settings: Config,
+    mut f: F,
+) -> Result<(), anyhow::Error>
+where
+    I: Iterator<Item = u8>,
+    F: FnMut(Chunk) -> Result<(), anyhow::Error>,
+{
+    let bytes_per_line = output_settings.bytes_per_line();
+    // a line is held back until the bytes needed to interpret its last characters are available
+    let lookahead = output_settings.code_page.lookahead();
+    let mut offset = 0;
+    let mut before: Vec<u8> = Vec::new();
+    let mut next_line = |data: &[u8], after: &[u8]| -> Result<(), anyhow::Error> {
+        f(Chunk {
+            offset,
+            before: &before,
+            data,
+            after,
+        })?;
+        offset += data.len() as u64;
+        before.extend_from_slice(data);
+        let keep = std::cmp::min(if offset.is_multiple_of(2) { 4 } else { 3 }, before.len());
+        before.drain(..before.len() - keep);
+        Ok(())
+    };
     let mut data: Vec<u8> = Vec::new();
-    let mut offset: usize = 0;
     for byte in sequence {
         data.push(byte);
-        if data.len() == output_settings.bytes_per_line() {
-            dump_line(
-                data.as_slice(),
-                writer,
-                output_settings.set_address(output_settings.start_address() + offset),
-            );
-            offset += data.len();
-            data.clear();
-        }
-    }
-    if !data.is_empty() {
-        dump_line(
-            data.as_slice(),
-            writer,
-            output_settings.set_address(output_settings.start_address() + offset),
-        );
-        data.clear();
+        if data.len() == bytes_per_line + lookahead {
+            next_line(&data[..bytes_per_line], &data[bytes_per_line..])?;
+            data.drain(..bytes_per_line);
+        }
+    }
+    for (index, line) in data.chunks(bytes_per_line).enumerate() {
+        let after = &data[std::cmp::min(data.len(), (index + 1) * bytes_per_line)..];
+        next_line(line, after)?;
     }
     Ok(())
 }
 
-fn dump_line(data: &[u8], writer: &mut dyn Write, output_settings: Config) {
-    // TODO: handle error properly in stead of using `unwrap()`. The
-    // error should propagate with `?` and the calling function should
-    // handle it.
-    let output_line = OutputLine::new(data).format(output_settings);
-    writer.write_fmt(format_args!("{}\n", output_line)).unwrap();
+/// Writes the lines of `dump_iterator` and keeps the state carried from one line to the next.
+struct LineWriter<'a> {
+    writer: &'a mut dyn Write,
+    output_settings: Config,
+    /// Last line which was written, used to detect repeated lines for autoskip.
+    previous: Vec<u8>,
+    /// Offset and preceding bytes of the last squeezed line.
+    skipped: Option<(u64, Vec<u8>)>,
+    /// Amount of lines written so far, including `*` lines, used to repeat the ruler.
+    rows: usize,
+}
+
+impl<'a> LineWriter<'a> {
+    fn new(writer: &'a mut dyn Write, output_settings: Config) -> Self {
+        LineWriter {
+            writer,
+            output_settings,
+            previous: Vec::new(),
+            skipped: None,
+            rows: 0,
+        }
+    }
+
+    fn write(&mut self, chunk: Chunk) -> Result<(), anyhow::Error> {
+        let autoskip = self.output_settings.autoskip && self.output_settings.show_address;
+        if autoskip && chunk.data == self.previous.as_slice() {
+            if self.skipped.is_none() {
+                self.start_row()?;
+                self.writer.write_all(b"*\n")?;
+            }
+            self.skipped = Some((chunk.offset, chunk.before.to_vec()));
+        } else {
+            self.write_line(chunk.data, chunk.before, chunk.after, chunk.offset)?;
+            self.skipped = None;
+            self.previous = chunk.data.to_vec();
+        }
+        Ok(())
+    }
+
+    fn finish(&mut self) -> Result<(), anyhow::Error> {
+        if let Some((offset, before)) = self.skipped.take() {
+            // the last squeezed line is repeated, so the dump still shows where the data ends
+            let previous = std::mem::take(&mut self.previous);
+            self.write_line(&previous, &before, &[], offset)?;
+        }
+        Ok(())
+    }
+
+    fn write_line(
+        &mut self,
+        data: &[u8],
+        before: &[u8],
+        after: &[u8],
+        offset: u64,
+    ) -> Result<(), anyhow::Error> {
+        self.start_row()?;
+        let address = self.output_settings.start_address() + offset;
+        let line = OutputLine::new(data)
+            .format(self.output_settings.set_address(address))
+            .context(before, after);
+        writeln!(self.writer, "{}", line)?;
+        Ok(())
+    }
+
+    /// Writes the ruler if it is due in front of the next line.
+    fn start_row(&mut self) -> Result<(), anyhow::Error> {
+        let due = match self.output_settings.ruler {
+            Some(0) => self.rows == 0,
+            Some(interval) => self.rows.is_multiple_of(interval),
+            None => false,
+        };
+        if due {
+            let labels = vec![0; self.output_settings.bytes_per_line()];
+            let ruler = OutputLine::new(&labels)
+                .format(self.output_settings)
+                .ruler();
+            writeln!(self.writer, "{}", ruler.to_string().trim_end())?;
+        }
+        self.rows += 1;
+        Ok(())
+    }
 }
 
 #[cfg(test)]
@@ -482,4 +1460,362 @@ mod test {
         // assert expectations
         assert_eq!(expected_output.as_bytes(), buffer.as_slice());
     }
+
+    #[test]
+    fn little_endian_output_format_on_a_single_line() {
+        let fixture = TestFixture::new();
+        let output_settings = Config::new()
+            .group_size(4)
+            .columns(2)
+            .endianness(Endianness::Little);
+        let output_line = OutputLine::new(fixture.data()).format(output_settings);
+        let expected_output = "00000000: 807FFF00 21014138  ....8A.!";
+        let mut buffer = String::new();
+        let result = write!(&mut buffer, "{}", output_line);
+        assert_eq!(Ok(()), result);
+        assert_eq!(expected_output, buffer);
+    }
+
+    #[test]
+    fn little_endian_output_format_for_a_single_line_with_padding() {
+        let fixture = TestFixture::new();
+        let output_settings = Config::new()
+            .group_size(4)
+            .columns(2)
+            .endianness(Endianness::Little);
+        let output_line = OutputLine::new(fixture.small_data()).format(output_settings);
+        let expected_output = "00000000: 2C50FF00       07  ..P,.";
+        let mut buffer = String::new();
+        let result = write!(&mut buffer, "{}", output_line);
+        assert_eq!(Ok(()), result);
+        assert_eq!(expected_output, buffer);
+    }
+
+    #[test]
+    fn little_endian_binary_output_format_on_a_single_line() {
+        let fixture = TestFixture::new();
+        let output_settings = Config::new()
+            .format(Format::Binary)
+            .group_size(2)
+            .columns(1)
+            .endianness(Endianness::Little);
+        let output_line = OutputLine::new(&fixture.data()[..2]).format(output_settings);
+        let expected_output = "00000000: 1111111100000000  ..";
+        let mut buffer = String::new();
+        let result = write!(&mut buffer, "{}", output_line);
+        assert_eq!(Ok(()), result);
+        assert_eq!(expected_output, buffer);
+    }
+
+    #[test]
+    fn byte_class_from_u8() {
+        assert_eq!(ByteClass::Null, ByteClass::from(0x00));
+        assert_eq!(ByteClass::AsciiPrintable, ByteClass::from(b'A'));
+        assert_eq!(ByteClass::AsciiPrintable, ByteClass::from(b'~'));
+        assert_eq!(ByteClass::AsciiWhitespace, ByteClass::from(b' '));
+        assert_eq!(ByteClass::AsciiWhitespace, ByteClass::from(b'\n'));
+        assert_eq!(ByteClass::AsciiControl, ByteClass::from(0x07));
+        assert_eq!(ByteClass::AsciiControl, ByteClass::from(0x7f));
+        assert_eq!(ByteClass::NonAscii, ByteClass::from(0x80));
+        assert_eq!(ByteClass::NonAscii, ByteClass::from(0xff));
+    }
+
+    #[test]
+    fn colored_output_format_for_a_single_line_with_padding() {
+        let output_settings = Config::new().color(true);
+        let output_line = OutputLine::new(&[0x00, 0x41]).format(output_settings);
+        let expected_output = format!(
+            "00000000: \x1b[90m00\x1b[0m \x1b[36m41\x1b[0m{}\x1b[90m.\x1b[0m\x1b[36mA\x1b[0m",
+            " ".repeat(20)
+        );
+        let mut buffer = String::new();
+        let result = write!(&mut buffer, "{}", output_line);
+        assert_eq!(Ok(()), result);
+        assert_eq!(expected_output, buffer);
+    }
+
+    #[test]
+    fn autoskip_squeezes_identical_lines() {
+        let mut data = vec![0xffu8; 40];
+        data.extend_from_slice(b"tail");
+        let mut output = Vec::new();
+        let output_settings = Config::new().columns(4).autoskip(true);
+        super::dump_iterator(data.into_iter(), &mut output, output_settings).unwrap();
+        let expected_output = "00000000: FF FF FF FF  ....\n\
+                               *\n\
+                               00000028: 74 61 69 6C  tail\n";
+        assert_eq!(expected_output, String::from_utf8(output).unwrap());
+    }
+
+    #[test]
+    fn autoskip_shows_last_line_of_trailing_run() {
+        let data = vec![0x00u8; 16];
+        let mut output = Vec::new();
+        let output_settings = Config::new().columns(4).autoskip(true);
+        super::dump_iterator(data.into_iter(), &mut output, output_settings).unwrap();
+        let expected_output = "00000000: 00 00 00 00  ....\n\
+                               *\n\
+                               0000000C: 00 00 00 00  ....\n";
+        assert_eq!(expected_output, String::from_utf8(output).unwrap());
+    }
+
+    #[test]
+    fn address_formats() {
+        let data = [0x41];
+        let cases = vec![
+            (Config::new().set_address(0xabc), "00000ABC: 41"),
+            (
+                Config::new().format(Format::Hex).set_address(0xabc),
+                "00000abc: 41",
+            ),
+            (
+                Config::new()
+                    .address_format(AddressFormat::Decimal)
+                    .set_address(0xabc),
+                "00002748: 41",
+            ),
+            (
+                Config::new()
+                    .address_format(AddressFormat::Octal)
+                    .address_width(4)
+                    .set_address(8),
+                "0010: 41",
+            ),
+            (Config::new().set_address(0x1_0000_0000), "100000000: 41"),
+            (Config::new().fit_address(0x1_0000_0000), "000000000: 41"),
+            (
+                Config::new().set_address(0x10).display_offset(0x0800_0000),
+                "08000010: 41",
+            ),
+            (
+                Config::new()
+                    .set_address(0x10)
+                    .display_offset(0x0800_0000)
+                    .show_file_offset(true),
+                "00000010 08000010: 41",
+            ),
+        ];
+        for (config, expected) in cases {
+            let line = OutputLine::new(&data)
+                .format(config.show_interpretation(false))
+                .to_string();
+            assert_eq!(expected, line.trim_end());
+        }
+    }
+
+    #[test]
+    fn interpretation_uses_code_page() {
+        let data = [0x14, 0x41, 0xc1, 0xe4];
+        let cases = vec![
+            (CodePage::Ascii, ".A.."),
+            (CodePage::Latin1, ".AÁä"),
+            (CodePage::Ebcdic, "..AU"),
+            (CodePage::Cp437, ".A┴Σ"),
+        ];
+        for (code_page, expected) in cases {
+            let config = Config::new().show_address(false).code_page(code_page);
+            let line = OutputLine::new(&data).format(config).to_string();
+            assert!(line.ends_with(expected), "{:?}: {}", code_page, line);
+        }
+    }
+
+    #[test]
+    fn utf8_characters_crossing_lines() {
+        let data = "ab€cd".as_bytes().to_vec();
+        let mut output = Vec::new();
+        let output_settings = Config::new().columns(4).code_page(CodePage::Utf8);
+        super::dump_iterator(data.into_iter(), &mut output, output_settings).unwrap();
+        let expected_output = "00000000: 61 62 E2 82  ab€•\n\
+                               00000004: AC 63 64     •cd\n";
+        assert_eq!(expected_output, String::from_utf8(output).unwrap());
+    }
+
+    #[test]
+    fn control_styles() {
+        let data = [0x00, 0x0a, 0x0d, 0x1b, 0x7f, 0x20, 0x41, 0xff];
+        let cases = vec![
+            (ControlStyle::Dot, "..... A."),
+            (ControlStyle::Picture, "␀␊␍␛␡ A."),
+            (ControlStyle::Caret, "^@^J^M^[^? A."),
+            (ControlStyle::Symbol, "⋄__▪▪ A×"),
+        ];
+        for (style, expected) in cases {
+            let config = Config::new().show_address(false).control_style(style);
+            let line = OutputLine::new(&data).format(config).to_string();
+            assert!(line.ends_with(expected), "{:?}: {}", style, line);
+        }
+    }
+
+    #[test]
+    fn value_types() {
+        let cases = vec![
+            (ValueType::U8, &[0xff][..], "255"),
+            (ValueType::I8, &[0xff][..], "-1"),
+            (ValueType::U16, &[0x12, 0x34][..], "4660"),
+            (ValueType::I16, &[0xff, 0xfe][..], "-2"),
+            (ValueType::U32, &[0, 0, 1, 0][..], "256"),
+            (ValueType::I32, &[0x80, 0, 0, 0][..], "-2147483648"),
+            (ValueType::U64, &[0, 0, 0, 0, 0, 0, 0, 42][..], "42"),
+            (ValueType::I64, &[0xff; 8][..], "-1"),
+            (ValueType::F32, &[0x3f, 0x80, 0, 0][..], "1"),
+            (
+                ValueType::F32,
+                &[0x00, 0x80, 0x00, 0x00][..],
+                "1.1754944e-38",
+            ),
+            (
+                ValueType::F64,
+                &[0x40, 0x09, 0x21, 0xfb, 0x54, 0x44, 0x2d, 0x18][..],
+                "3.141592653589793",
+            ),
+        ];
+        for (value_type, bytes, expected) in cases {
+            assert_eq!(expected, value_type.format(bytes, Endianness::Big));
+            assert!(expected.len() <= value_type.width());
+        }
+        assert_eq!(
+            "-2",
+            ValueType::I16.format(&[0xfe, 0xff], Endianness::Little)
+        );
+    }
+
+    #[test]
+    fn values_pane() {
+        let data = [0x01, 0x00, 0xff, 0xff, 0x00];
+        let config = Config::new()
+            .group_size(2)
+            .columns(4)
+            .endianness(Endianness::Little)
+            .values(Some(ValueType::I16));
+        let line = OutputLine::new(&data).format(config).to_string();
+        let expected_line = "00000000: 0001 FFFF   00            1     -1                .....";
+        assert_eq!(expected_line, line);
+        let line = OutputLine::new(&data)
+            .format(config.show_bytes(false))
+            .to_string();
+        assert_eq!("00000000:      1     -1                .....", line);
+    }
+
+    #[test]
+    fn panes_side_by_side() {
+        let config = Config::new()
+            .columns(4)
+            .formats(&[Format::Hex, Format::Decimal]);
+        let line = OutputLine::new(&[0x41, 0xff, 0x0a])
+            .format(config)
+            .to_string();
+        let expected_line = "00000000: 41 ff 0a     065 255 010      A..";
+        assert_eq!(expected_line, line);
+        let lines = OutputLine::new(&[0x41, 0xff, 0x0a, 0x42])
+            .format(config)
+            .to_string();
+        assert_eq!("00000000: 41 ff 0a 42  065 255 010 066  A..B", lines);
+    }
+
+    #[test]
+    #[should_panic]
+    fn formats_panics_on_too_many_panes() {
+        Config::new().formats(&[Format::Hex; MAX_PANES + 1]);
+    }
+
+    #[test]
+    fn highlighted_bytes() {
+        let highlight = [false, true, false];
+        let config = Config::new().columns(4).group_size(1);
+        let line = OutputLine::new(&[0x41, 0x42, 0x43])
+            .format(config)
+            .highlight(&highlight);
+        let markers = OutputLine::new(&[0x41, 0x42, 0x43])
+            .format(config)
+            .highlight(&highlight)
+            .markers();
+        assert_eq!("00000000: 41 42 43     ABC", line.to_string());
+        assert_eq!("             ^^         ^ ", markers.to_string());
+        let colored = OutputLine::new(&[0x41, 0x42])
+            .format(config.color(true).show_interpretation(false))
+            .highlight(&highlight);
+        assert!(colored
+            .to_string()
+            .contains("\x1b[36m41\x1b[0m \x1b[7;31m42\x1b[0m"));
+    }
+
+    #[test]
+    fn html_line() {
+        let config = Config::new()
+            .columns(2)
+            .set_address(0x1a0)
+            .color(true)
+            .html(true);
+        let line = OutputLine::new(b"<").format(config).to_string();
+        let cell = "<span class=\"printable\" data-i=\"0\" title=\"dec 60, oct 74, bin 00111100\">";
+        let expected_line = format!(
+            "<span class=\"row\" id=\"0x1A0\"><a href=\"#0x1A0\">000001A0</a>: \
+             {}3C</span>     {}&lt;</span></span>",
+            cell, cell
+        );
+        assert_eq!(expected_line, line);
+    }
+
+    #[test]
+    fn html_page() {
+        let mut buffer: Vec<u8> = Vec::new();
+        let config = Config::new().columns(4).html(true);
+        super::dump_iterator(b"hello".iter().cloned(), &mut buffer, config).unwrap();
+        let page = String::from_utf8(buffer).unwrap();
+        assert!(page.starts_with("<!DOCTYPE html>"));
+        assert!(page.ends_with("</html>\n"));
+        assert!(page.contains("<span class=\"row\" id=\"0x0\">"));
+        assert!(page.contains("<span class=\"row\" id=\"0x4\">"));
+        assert!(!page.contains('\x1b'));
+    }
+
+    #[test]
+    fn ruler_labels() {
+        let labels = [0; 4];
+        let config = Config::new().columns(2).group_size(2);
+        let ruler = OutputLine::new(&labels).format(config).ruler().to_string();
+        assert_eq!("#         0001 0203  0123", ruler);
+        let config = config
+            .formats(&[Format::Hex, Format::Binary])
+            .endianness(Endianness::Little)
+            .show_interpretation(false);
+        let ruler = OutputLine::new(&labels).format(config).ruler().to_string();
+        let expected_ruler = "#         0100 0302        01      00       03      02 ";
+        assert_eq!(expected_ruler, ruler);
+        let wide_labels = [0; 20];
+        let config = Config::new()
+            .columns(20)
+            .address_format(AddressFormat::Decimal);
+        let ruler = OutputLine::new(&wide_labels)
+            .format(config)
+            .ruler()
+            .to_string();
+        assert!(ruler.ends_with("  01234567890123456789"));
+        assert!(ruler.contains("09 10 11"));
+    }
+
+    #[test]
+    fn repeated_ruler() {
+        let mut buffer: Vec<u8> = Vec::new();
+        let config = Config::new()
+            .columns(2)
+            .show_interpretation(false)
+            .ruler(Some(2));
+        super::dump_iterator(b"abcdef".iter().cloned(), &mut buffer, config).unwrap();
+        let expected_output = "#         00 01\n\
+                               00000000: 61 62 \n\
+                               00000002: 63 64 \n\
+                               #         00 01\n\
+                               00000004: 65 66 \n";
+        assert_eq!(expected_output, String::from_utf8(buffer).unwrap());
+        let mut buffer: Vec<u8> = Vec::new();
+        let config = Config::new()
+            .columns(2)
+            .control_style(ControlStyle::Caret)
+            .ruler(Some(0));
+        super::dump_iterator(b"\x01b".iter().cloned(), &mut buffer, config).unwrap();
+        let expected_output = "#         00 01\n\
+                               00000000: 01 62  ^Ab\n";
+        assert_eq!(expected_output, String::from_utf8(buffer).unwrap());
+    }
 }
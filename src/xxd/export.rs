@@ -0,0 +1,175 @@
+//! The export module contains code related to dumping data as structured records, which can be
+//! processed by other tools without parsing the text of a dump.
+use crate::dump::{for_each_line, Config, OutputLine};
+use serde::Serialize;
+use std::io::Write;
+
+/// Enum which provides all structured formats supported by the export module.
+#[derive(Debug, PartialEq, Clone, Copy)]
+pub enum ExportFormat {
+    /// One JSON object per line.
+    Ndjson,
+    /// A single JSON array containing one object per line.
+    Json,
+    /// A header followed by one row per line.
+    Csv,
+}
+
+impl From<String> for ExportFormat {
+    fn from(format_string: String) -> Self {
+        match format_string.as_ref() {
+            "ndjson" => ExportFormat::Ndjson,
+            "json" => ExportFormat::Json,
+            "csv" => ExportFormat::Csv,
+            _ => panic!("Invalid export format"),
+        }
+    }
+}
+
+const CSV_HEADER: &str = "offset,bytes,cells,interpretation";
+
+/// A single line of a dump.
+#[derive(Serialize)]
+struct Record {
+    offset: u64,
+    bytes: Vec<u8>,
+    /// Formatted groups of bytes, as in the first pane of the dump.
+    cells: Vec<String>,
+    interpretation: String,
+}
+
+impl Record {
+    /// The bytes are written as hex string and the cells are separated by spaces.
+    fn to_csv(&self) -> String {
+        let bytes: String = self.bytes.iter().map(|b| format!("{:02x}", b)).collect();
+        format!(
+            "{},{},{},{}",
+            self.offset,
+            bytes,
+            csv_field(&self.cells.join(" ")),
+            csv_field(&self.interpretation)
+        )
+    }
+}
+
+/// Quotes a field if it contains characters with a special meaning in CSV (RFC 4180).
+fn csv_field(text: &str) -> String {
+    let needs_quotes =
+        text.starts_with(' ') || text.ends_with(' ') || text.contains([',', '"', '\n', '\r']);
+    if needs_quotes {
+        format!("\"{}\"", text.replace('"', "\"\""))
+    } else {
+        text.to_string()
+    }
+}
+
+/// Writes one record per line of the dump, the lines are split according to the `columns` and
+/// `group_size` of `output_settings`.
+///
+/// Every record contains the offset of the line, its raw bytes, the formatted cells and the
+/// interpretation column without colors.
+pub fn export_iterator<I>(
+    sequence: I,
+    writer: &mut dyn Write,
+    output_settings: Config,
+    format: ExportFormat,
+) -> Result<(), anyhow::Error>
+where
+    I: Iterator<Item = u8>,
+{
+    match format {
+        ExportFormat::Json => writeln!(writer, "[")?,
+        ExportFormat::Csv => writeln!(writer, "{}", CSV_HEADER)?,
+        ExportFormat::Ndjson => {}
+    }
+    let mut first = true;
+    for_each_line(sequence, output_settings, |chunk| {
+        let line = OutputLine::new(chunk.data)
+            .format(output_settings)
+            .context(chunk.before, chunk.after);
+        let record = Record {
+            offset: output_settings.start_address() + chunk.offset,
+            bytes: chunk.data.to_vec(),
+            cells: line.cells(),
+            interpretation: line.interpretation(),
+        };
+        match format {
+            ExportFormat::Json => {
+                if !first {
+                    writeln!(writer, ",")?;
+                }
+                serde_json::to_writer(&mut *writer, &record)?;
+            }
+            ExportFormat::Ndjson => {
+                serde_json::to_writer(&mut *writer, &record)?;
+                writeln!(writer)?;
+            }
+            ExportFormat::Csv => writeln!(writer, "{}", record.to_csv())?,
+        }
+        first = false;
+        Ok(())
+    })?;
+    if format == ExportFormat::Json {
+        if !first {
+            writeln!(writer)?;
+        }
+        writeln!(writer, "]")?;
+    }
+    Ok(())
+}
+
+#[cfg(test)]
+mod test {
+
+    use super::*;
+    use crate::dump::Endianness;
+
+    fn export(data: &[u8], output_settings: Config, format: ExportFormat) -> String {
+        let mut output = Vec::new();
+        export_iterator(data.iter().cloned(), &mut output, output_settings, format).unwrap();
+        String::from_utf8(output).unwrap()
+    }
+
+    #[test]
+    fn ndjson_records() {
+        let output_settings = Config::new().columns(2).group_size(2).set_address(0x10);
+        let output = export(b"Hello\n", output_settings, ExportFormat::Ndjson);
+        let expected_output = concat!(
+            "{\"offset\":16,\"bytes\":[72,101,108,108],\"cells\":[\"4865\",\"6C6C\"],",
+            "\"interpretation\":\"Hell\"}\n",
+            "{\"offset\":20,\"bytes\":[111,10],\"cells\":[\"6F0A\"],",
+            "\"interpretation\":\"o.\"}\n"
+        );
+        assert_eq!(expected_output, output);
+    }
+
+    #[test]
+    fn json_document() {
+        let output_settings = Config::new().columns(2).color(true);
+        let output = export(b"\"\\x", output_settings, ExportFormat::Json);
+        let expected_output = concat!(
+            "[\n",
+            "{\"offset\":0,\"bytes\":[34,92],\"cells\":[\"22\",\"5C\"],",
+            "\"interpretation\":\"\\\"\\\\\"},\n",
+            "{\"offset\":2,\"bytes\":[120],\"cells\":[\"78\"],\"interpretation\":\"x\"}\n",
+            "]\n"
+        );
+        assert_eq!(expected_output, output);
+        assert_eq!("[\n]\n", export(b"", output_settings, ExportFormat::Json));
+    }
+
+    #[test]
+    fn csv_rows() {
+        let output_settings = Config::new()
+            .columns(2)
+            .group_size(2)
+            .endianness(Endianness::Little);
+        let output = export(b"a,\"b ", output_settings, ExportFormat::Csv);
+        let expected_output = concat!(
+            "offset,bytes,cells,interpretation\n",
+            "0,612c2262,2C61 6222,\"a,\"\"b\"\n",
+            "4,20,20,\" \"\n"
+        );
+        assert_eq!(expected_output, output);
+    }
+}
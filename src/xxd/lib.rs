@@ -1,3 +1,8 @@
+pub mod annotate;
+pub mod codepage;
 pub mod convert;
+pub mod diff;
 pub mod dump;
+pub mod export;
 pub mod generate;
+pub mod multi;
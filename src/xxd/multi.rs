@@ -0,0 +1,182 @@
+//! The multi module contains code related to dumping several inputs one after another, each dump
+//! is preceded by a header naming its input (like `head`).
+use crate::dump::Config;
+use std::io::Write;
+
+/// Whether the input `name` is standard input, `-` or its alias `stdin`.
+pub fn is_stdin(name: &str) -> bool {
+    name == "-" || name == "stdin"
+}
+
+/// Size of the file `name`, `None` if it isn't a regular file (e.g. standard input or a pipe).
+pub fn file_size(name: &str) -> Option<u64> {
+    match std::fs::metadata(name) {
+        Ok(metadata) if !is_stdin(name) && metadata.is_file() => Some(metadata.len()),
+        _ => None,
+    }
+}
+
+/// Header which precedes the dump of the input `name`, it shows the size if it is known.
+pub fn header(name: &str, size: Option<u64>) -> String {
+    match size {
+        _ if is_stdin(name) => "==> standard input <==".to_string(),
+        Some(size) => format!("==> {} ({} bytes) <==", name, size),
+        None => format!("==> {} <==", name),
+    }
+}
+
+/// Amount of bytes dumped from inputs of the given `sizes` if `seek` bytes of every input are
+/// skipped and at most `length` bytes are dumped, `None` if the size of an input is unknown.
+///
+/// With continuous addresses the sizes of all inputs add up, otherwise the largest one counts.
+pub fn dumped_size(sizes: &[Option<u64>], seek: u64, length: u64, continuous: bool) -> Option<u64> {
+    let mut dumped = Vec::new();
+    for size in sizes {
+        dumped.push(std::cmp::min(size.as_ref()?.saturating_sub(seek), length));
+    }
+    if continuous {
+        Some(dumped.iter().sum())
+    } else {
+        dumped.into_iter().max()
+    }
+}
+
+/// Dumps several inputs one after another.
+pub struct MultiDump {
+    output_settings: Config,
+    /// Headers are only written if more than one input is dumped.
+    headers: bool,
+    continuous: bool,
+    /// Start address of the next input.
+    address: u64,
+}
+
+impl MultiDump {
+    pub fn new(output_settings: Config, inputs: usize) -> Self {
+        MultiDump {
+            output_settings,
+            headers: inputs > 1,
+            continuous: false,
+            address: output_settings.start_address(),
+        }
+    }
+
+    /// Continues the addresses of every input where the previous one ended, instead of starting
+    /// each one at the start address.
+    pub fn continuous(mut self, continuous: bool) -> Self {
+        self.continuous = continuous;
+        self
+    }
+
+    /// Writes the header of the input `name` followed by the dump of `sequence`, which `dump`
+    /// writes using the settings for this input.
+    pub fn dump<I, F>(
+        &mut self,
+        name: &str,
+        sequence: I,
+        writer: &mut dyn Write,
+        dump: F,
+    ) -> Result<(), anyhow::Error>
+    where
+        I: Iterator<Item = u8>,
+        F: FnOnce(
+            &mut dyn Iterator<Item = u8>,
+            &mut dyn Write,
+            Config,
+        ) -> Result<(), anyhow::Error>,
+    {
+        if self.headers {
+            writeln!(writer, "{}", header(name, file_size(name)))?;
+        }
+        let mut dumped = 0;
+        let mut bytes = sequence.inspect(|_| dumped += 1);
+        dump(
+            &mut bytes,
+            writer,
+            self.output_settings.set_address(self.address),
+        )?;
+        if self.continuous {
+            self.address += dumped;
+        }
+        Ok(())
+    }
+}
+
+#[cfg(test)]
+mod test {
+
+    use super::*;
+    use crate::dump::dump_iterator;
+
+    fn dump_all(inputs: &[(&str, &[u8])], multi_dump: MultiDump) -> String {
+        let mut multi_dump = multi_dump;
+        let mut output = Vec::new();
+        for (name, data) in inputs.iter() {
+            multi_dump
+                .dump(
+                    name,
+                    data.iter().cloned(),
+                    &mut output,
+                    |bytes, writer, settings| dump_iterator(bytes, writer, settings),
+                )
+                .unwrap();
+        }
+        String::from_utf8(output).unwrap()
+    }
+
+    #[test]
+    fn headers() {
+        assert_eq!("==> standard input <==", header("-", None));
+        assert_eq!("==> standard input <==", header("stdin", None));
+        assert_eq!("==> a.bin (16 bytes) <==", header("a.bin", Some(16)));
+        assert_eq!("==> /dev/fd/3 <==", header("/dev/fd/3", None));
+        assert_eq!(None, file_size("stdin"));
+        assert_eq!(None, file_size("src"));
+    }
+
+    #[test]
+    fn dumped_sizes() {
+        let sizes = [Some(20), Some(5), Some(40)];
+        assert_eq!(Some(40), dumped_size(&sizes, 0, u64::MAX, false));
+        assert_eq!(Some(65), dumped_size(&sizes, 0, u64::MAX, true));
+        assert_eq!(Some(40), dumped_size(&sizes, 10, u64::MAX, true));
+        assert_eq!(Some(21), dumped_size(&sizes, 0, 8, true));
+        assert_eq!(None, dumped_size(&[Some(20), None], 0, u64::MAX, false));
+    }
+
+    #[test]
+    fn single_input_without_header() {
+        let config = Config::new().group_size(2);
+        let output = dump_all(&[("-", b"hi")], MultiDump::new(config, 1));
+        assert_eq!(
+            "00000000: 6869                                     hi\n",
+            output
+        );
+    }
+
+    #[test]
+    fn inputs_with_headers() {
+        let config = Config::new().group_size(2).columns(2).set_address(0x10);
+        let inputs: [(&str, &[u8]); 2] = [("a.bin", b"hello"), ("-", b"xxd")];
+        let expected_output = concat!(
+            "==> a.bin <==\n",
+            "00000010: 6865 6C6C  hell\n",
+            "00000014: 6F         o\n",
+            "==> standard input <==\n",
+            "00000010: 7878 64    xxd\n",
+        );
+        assert_eq!(
+            expected_output,
+            dump_all(&inputs, MultiDump::new(config, 2))
+        );
+        let expected_output = concat!(
+            "==> a.bin <==\n",
+            "00000010: 6865 6C6C  hell\n",
+            "00000014: 6F         o\n",
+            "==> standard input <==\n",
+            "00000015: 7878 64    xxd\n",
+        );
+        let multi_dump = MultiDump::new(config, 2).continuous(true);
+        assert_eq!(expected_output, dump_all(&inputs, multi_dump));
+    }
+}